@@ -67,9 +67,17 @@ pub struct Assets {
     pub fence: Texture,
     pub wall: Texture,
     pub water: Texture,
+    pub bridge: Texture,
     pub fish: Texture,
+    #[asset(path = "fence_tiles.png")]
+    pub fence_tiles: Texture,
+    #[asset(path = "wall_tiles.png")]
+    pub wall_tiles: Texture,
+    #[asset(path = "water_tiles.png")]
+    pub water_tiles: Texture,
     #[asset(path = "levels/level*.json", range = "1..=9")]
     pub levels: Vec<Level>,
+    pub bindings: KeyBindings,
     pub font: Texture,
     pub loss: geng::Sound,
     pub win: geng::Sound,
@@ -88,9 +96,54 @@ impl Assets {
             EntityType::Cheese => &self.cheese,
             EntityType::Fence => &self.fence,
             EntityType::Wall => &self.wall,
-            EntityType::Water => &self.water,
             EntityType::Bone => &self.bone,
             EntityType::Fish => &self.fish,
         }
     }
 }
+
+/// Auto-tile atlases are 4x4 grids of variants, indexed by [`NeighbourMask`].
+const AUTO_TILE_COLUMNS: usize = 4;
+
+/// Bit set of sides that have a neighbour of the same type.
+#[derive(Clone, Copy)]
+pub struct NeighbourMask(pub usize);
+
+impl NeighbourMask {
+    pub const UP: usize = 1;
+    pub const RIGHT: usize = 2;
+    pub const DOWN: usize = 4;
+    pub const LEFT: usize = 8;
+
+    pub fn from_fn(mut has_neighbour: impl FnMut(Vec2<i32>) -> bool) -> Self {
+        let mut mask = 0;
+        for &(side, direction) in &[
+            (Self::UP, vec2(0, 1)),
+            (Self::RIGHT, vec2(1, 0)),
+            (Self::DOWN, vec2(0, -1)),
+            (Self::LEFT, vec2(-1, 0)),
+        ] {
+            if has_neighbour(direction) {
+                mask |= side;
+            }
+        }
+        Self(mask)
+    }
+    /// Texture matrix selecting this variant from an auto-tile atlas.
+    pub fn texture_matrix(self) -> Mat4<f32> {
+        let tx = self.0 % AUTO_TILE_COLUMNS;
+        let ty = self.0 / AUTO_TILE_COLUMNS;
+        Mat4::scale_uniform(1.0 / AUTO_TILE_COLUMNS as f32)
+            * Mat4::translate(vec3(tx as f32, ty as f32, 0.0))
+    }
+}
+
+impl Assets {
+    pub fn auto_tiles(&self, entity: EntityType) -> Option<&ugli::Texture> {
+        match entity {
+            EntityType::Fence => Some(&self.fence_tiles),
+            EntityType::Wall => Some(&self.wall_tiles),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PaletteBinding {
+    pub entity_type: Option<EntityType>,
+    pub key: geng::Key,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ActionBinding {
+    pub action: Action,
+    pub key: geng::Key,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GamepadBinding {
+    pub action: Action,
+    pub button: GamepadButton,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub actions: Vec<ActionBinding>,
+    pub gamepad: Vec<GamepadBinding>,
+    #[serde(deserialize_with = "deserialize_palette")]
+    pub palette: Vec<PaletteBinding>,
+}
+
+/// Palette entries for entity types that no longer exist are dropped.
+fn deserialize_palette<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<PaletteBinding>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredBinding {
+        Known(PaletteBinding),
+        Unknown(serde::de::IgnoredAny),
+    }
+    let bindings: Vec<StoredBinding> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(bindings
+        .into_iter()
+        .filter_map(|binding| match binding {
+            StoredBinding::Known(binding) => Some(binding),
+            StoredBinding::Unknown(_) => None,
+        })
+        .collect())
+}
+
+impl KeyBindings {
+    /// Fills in defaults for whatever was added since these bindings were saved:
+    /// actions without any keys or buttons, and palette entries for new entity types.
+    /// Default keys that are already taken by something else are left out.
+    pub fn merge_defaults(&mut self, defaults: &KeyBindings) {
+        let unbound: Vec<Action> = Action::into_enum_iter()
+            .filter(|&action| self.action_keys(action).is_empty())
+            .collect();
+        for binding in &defaults.actions {
+            let key_taken = self.actions.iter().any(|other| {
+                other.key == binding.key && other.action.shares_context(binding.action)
+            });
+            if unbound.contains(&binding.action) && !key_taken {
+                self.actions.push(binding.clone());
+            }
+        }
+        let no_buttons: Vec<Action> = Action::into_enum_iter()
+            .filter(|&action| self.gamepad.iter().all(|other| other.action != action))
+            .collect();
+        for binding in &defaults.gamepad {
+            let button_taken = self
+                .gamepad
+                .iter()
+                .any(|other| other.button == binding.button);
+            if no_buttons.contains(&binding.action) && !button_taken {
+                self.gamepad.push(binding.clone());
+            }
+        }
+        for binding in &defaults.palette {
+            // A taken key still shows up in the settings so it can be rebound there
+            if self.palette_key(binding.entity_type).is_none() {
+                self.palette.push(binding.clone());
+            }
+        }
+    }
+    /// First action bound to `key` that passes `filter`,
+    /// since the game and the editor may share keys.
+    pub fn key_action(&self, key: geng::Key, filter: impl Fn(Action) -> bool) -> Option<Action> {
+        self.actions
+            .iter()
+            .filter(|binding| binding.key == key && filter(binding.action))
+            .map(|binding| binding.action)
+            .next()
+    }
+    pub fn button_action(&self, button: GamepadButton) -> Option<Action> {
+        self.gamepad
+            .iter()
+            .find(|binding| binding.button == button)
+            .map(|binding| binding.action)
+    }
+    pub fn action_keys(&self, action: Action) -> Vec<geng::Key> {
+        self.actions
+            .iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| binding.key)
+            .collect()
+    }
+    pub fn action_name(&self, action: Action) -> String {
+        let keys: Vec<String> = self.action_keys(action).into_iter().map(key_name).collect();
+        if keys.is_empty() {
+            "unbound".to_owned()
+        } else {
+            keys.join("/")
+        }
+    }
+    /// Unbinds `key` from other actions that are active together with `action`.
+    /// Returns a description of every binding that was removed.
+    fn take_key(&mut self, action: Action, key: geng::Key) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        self.actions.retain(|binding| {
+            let conflict = binding.key == key
+                && binding.action != action
+                && binding.action.shares_context(action);
+            if conflict {
+                conflicts.push(format!(
+                    "{} is no longer on {}",
+                    binding.action.name(),
+                    key_name(key)
+                ));
+            }
+            !conflict
+        });
+        if action.in_editor() {
+            conflicts.extend(self.palette_conflicts(key));
+        }
+        conflicts
+    }
+    /// Palette entries on `key`, which hide editor actions on the same key.
+    fn palette_conflicts(&self, key: geng::Key) -> Vec<String> {
+        self.palette
+            .iter()
+            .filter(|binding| binding.key == key)
+            .map(|binding| {
+                format!(
+                    "{} is also {} in the editor palette, which goes first",
+                    key_name(key),
+                    palette_name(binding.entity_type)
+                )
+            })
+            .collect()
+    }
+    /// Moves a palette entry to `key`, swapping keys with the entry that had it.
+    /// Returns a description of the conflicts.
+    pub fn rebind_palette(&mut self, idx: usize, key: geng::Key) -> Vec<String> {
+        let old_key = self.palette[idx].key;
+        let mut conflicts = Vec::new();
+        for (other_idx, binding) in self.palette.iter_mut().enumerate() {
+            if other_idx != idx && binding.key == key {
+                binding.key = old_key;
+                conflicts.push(format!(
+                    "{} moved to {}",
+                    palette_name(binding.entity_type),
+                    key_name(old_key)
+                ));
+            }
+        }
+        self.palette[idx].key = key;
+        for binding in &self.actions {
+            if binding.key == key && binding.action.in_editor() {
+                conflicts.push(format!(
+                    "{} hides {} in the editor",
+                    key_name(key),
+                    binding.action.name()
+                ));
+            }
+        }
+        conflicts
+    }
+    /// Replaces the keys of `action`, taking the key away from anything it conflicts with.
+    /// Returns a description of the conflicts.
+    pub fn rebind(&mut self, action: Action, key: geng::Key) -> Vec<String> {
+        self.actions.retain(|binding| binding.action != action);
+        let conflicts = self.take_key(action, key);
+        self.actions.push(ActionBinding { action, key });
+        conflicts
+    }
+    pub fn movement_preset(&self) -> Option<MovementPreset> {
+        MovementPreset::into_enum_iter().find(|&preset| {
+            MOVE_ACTIONS.iter().enumerate().all(|(i, &action)| {
+                let mut keys = vec![ARROW_KEYS[i]];
+                keys.extend(preset.keys().map(|keys| keys[i]));
+                self.action_keys(action) == keys
+            })
+        })
+    }
+    /// Preset keys are taken away from other actions on the same key.
+    /// Returns a description of the conflicts.
+    pub fn set_movement_preset(&mut self, preset: MovementPreset) -> Vec<String> {
+        self.actions
+            .retain(|binding| !MOVE_ACTIONS.contains(&binding.action));
+        let mut conflicts = Vec::new();
+        for (i, &action) in MOVE_ACTIONS.iter().enumerate() {
+            let mut keys = vec![ARROW_KEYS[i]];
+            keys.extend(preset.keys().map(|keys| keys[i]));
+            for key in keys {
+                conflicts.extend(self.take_key(action, key));
+                self.actions.push(ActionBinding { action, key });
+            }
+        }
+        conflicts
+    }
+    /// Short description of the movement keys for the controls hint.
+    pub fn movement_name(&self) -> String {
+        match self.movement_preset() {
+            Some(preset) => preset.name().to_owned(),
+            None => MOVE_ACTIONS
+                .iter()
+                .map(|&action| self.action_name(action))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+    pub fn palette_key(&self, entity_type: Option<EntityType>) -> Option<geng::Key> {
+        self.palette
+            .iter()
+            .find(|binding| binding.entity_type == entity_type)
+            .map(|binding| binding.key)
+    }
+    pub fn palette_entity(&self, key: geng::Key) -> Option<Option<EntityType>> {
+        self.palette
+            .iter()
+            .find(|binding| binding.key == key)
+            .map(|binding| binding.entity_type)
+    }
+}
+
+impl geng::LoadAsset for KeyBindings {
+    fn load(geng: &Rc<Geng>, path: &str) -> geng::AssetFuture<Self> {
+        Box::pin(
+            <String as geng::LoadAsset>::load(geng, path).map(|s| Ok(serde_json::from_str(&s?)?)),
+        )
+    }
+    const DEFAULT_EXT: Option<&'static str> = Some("json");
+}
+
+pub fn key_name(key: geng::Key) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Num") {
+        Some(digit) => digit.to_owned(),
+        None => name,
+    }
+}
+
+pub fn palette_name(entity_type: Option<EntityType>) -> &'static str {
+    match entity_type {
+        Some(entity_type) => entity_type.name(),
+        None => "Erase",
+    }
+}
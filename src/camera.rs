@@ -1,10 +1,35 @@
 use super::*;
 
+const MAX_FIT_FOV: f32 = 16.0;
+const FOLLOW_FOV: f32 = 12.0;
+const DEAD_ZONE_WIDTH: f32 = 2.0;
+const DEAD_ZONE_HEIGHT: f32 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    /// Fits the level if it is small enough, follows the player otherwise.
+    Auto,
+    FitLevel,
+    FollowPlayer,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Auto => CameraMode::FitLevel,
+            CameraMode::FitLevel => CameraMode::FollowPlayer,
+            CameraMode::FollowPlayer => CameraMode::Auto,
+        }
+    }
+}
+
 pub struct Camera {
     pub center: Vec2<f32>,
     pub target_position: Vec2<f32>,
     pub fov: f32,
     pub target_fov: f32,
+    pub mode: CameraMode,
+    initialized: bool,
 }
 
 impl Camera {
@@ -14,8 +39,83 @@ impl Camera {
             fov,
             target_fov: fov,
             target_position: vec2(0.0, 0.0),
+            mode: CameraMode::Auto,
+            initialized: false,
         }
     }
+    /// Moves the target position and fov according to the mode.
+    /// The first call snaps the camera to the target.
+    pub fn update_target(&mut self, level: &Level, framebuffer_size: Vec2<f32>) {
+        let fit = Self::fit(level, framebuffer_size);
+        let player = level
+            .get_player()
+            .map(|player| player.render_pos + vec2(0.5, 0.5));
+        let follow = match (self.mode, fit, player) {
+            (CameraMode::FitLevel, _, _) | (_, _, None) => false,
+            (CameraMode::FollowPlayer, _, Some(_)) => true,
+            (CameraMode::Auto, Some((_, fov)), Some(_)) => fov > MAX_FIT_FOV,
+            (CameraMode::Auto, None, Some(_)) => true,
+        };
+        match (follow, player) {
+            (true, Some(player)) => {
+                let offset = player - self.target_position;
+                let dead_zone = vec2(DEAD_ZONE_WIDTH, DEAD_ZONE_HEIGHT);
+                let outside = vec2(
+                    offset.x - clamp(offset.x, -dead_zone.x..=dead_zone.x),
+                    offset.y - clamp(offset.y, -dead_zone.y..=dead_zone.y),
+                );
+                if !self.initialized {
+                    self.target_position = player;
+                } else {
+                    self.target_position += outside;
+                }
+                self.target_fov = FOLLOW_FOV;
+            }
+            _ => {
+                if let Some((center, fov)) = fit {
+                    self.target_position = center;
+                    self.target_fov = fov;
+                }
+            }
+        }
+        if !self.initialized {
+            self.initialized = true;
+            self.snap();
+        }
+    }
+    /// Center and fov that fit the level bounds, or all entities if there are none.
+    fn fit(level: &Level, framebuffer_size: Vec2<f32>) -> Option<(Vec2<f32>, f32)> {
+        let mut min_x = i32::MAX;
+        let mut min_y = i32::MAX;
+        let mut max_x = i32::MIN;
+        let mut max_y = i32::MIN;
+        if let Some(bounds) = &level.bounds {
+            min_x = bounds.min.x;
+            min_y = bounds.min.y;
+            max_x = bounds.max.x;
+            max_y = bounds.max.y;
+        } else {
+            for entity in level.entities.values() {
+                min_x = min_x.min(entity.position.x);
+                min_y = min_y.min(entity.position.y);
+                max_x = max_x.max(entity.position.x);
+                max_y = max_y.max(entity.position.y);
+            }
+        }
+        if min_x > max_x {
+            return None;
+        }
+        max_x += 1;
+        max_y += 1;
+        let aspect = framebuffer_size.x / framebuffer_size.y;
+        let fov = ((max_y - min_y + 5) as f32).max((max_x - min_x + 5) as f32 / aspect);
+        let center = vec2(min_x + max_x, min_y + max_y).map(|x| x as f32) / 2.0;
+        Some((center, fov))
+    }
+    pub fn snap(&mut self) {
+        self.center = self.target_position;
+        self.fov = self.target_fov;
+    }
     pub fn update(&mut self, delta_time: f32) {
         let delta_time = delta_time * 5.0;
         self.fov += (self.target_fov - self.fov) * delta_time.min(1.0);
@@ -0,0 +1,261 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InspectorRow {
+    EntityType,
+    Controller,
+    ViewRadius,
+    Chain,
+    ChainDistance,
+    ChainOrigin,
+}
+
+const ROW_HEIGHT: f32 = 32.0;
+const PANEL_WIDTH: f32 = 400.0;
+
+pub struct Inspector {
+    selected: Option<Id>,
+    picking_origin: bool,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self {
+            selected: None,
+            picking_origin: false,
+        }
+    }
+    pub fn select(&mut self, level: &Level, position: Vec2<i32>) {
+        self.selected = level.get_entity(position).map(|(&id, _)| id);
+        self.picking_origin = false;
+    }
+    fn rows(entity: &Entity) -> Vec<InspectorRow> {
+        let mut rows = vec![InspectorRow::EntityType, InspectorRow::Controller];
+        if let Some(controller) = &entity.controller {
+            match &controller.controller_type {
+                ControllerType::Player => {}
+                ControllerType::Dog { chain } => {
+                    rows.push(InspectorRow::ViewRadius);
+                    rows.push(InspectorRow::Chain);
+                    if chain.is_some() {
+                        rows.push(InspectorRow::ChainDistance);
+                        rows.push(InspectorRow::ChainOrigin);
+                    }
+                }
+                _ => rows.push(InspectorRow::ViewRadius),
+            }
+        }
+        rows
+    }
+    fn row_aabb(framebuffer_size: Vec2<f32>, rows: usize, idx: usize) -> AABB<f32> {
+        AABB::pos_size(
+            vec2(
+                framebuffer_size.x - PANEL_WIDTH,
+                (rows - idx - 1) as f32 * ROW_HEIGHT,
+            ),
+            vec2(PANEL_WIDTH, ROW_HEIGHT),
+        )
+    }
+    fn row_text(&self, row: InspectorRow, entity: &Entity) -> String {
+        let controller = entity.controller.as_ref();
+        let chain = controller.and_then(|controller| match &controller.controller_type {
+            ControllerType::Dog { chain } => chain.as_ref(),
+            _ => None,
+        });
+        match row {
+            InspectorRow::EntityType => format!(
+                "{} at ({}, {})",
+                entity.entity_type.name(),
+                entity.position.x,
+                entity.position.y
+            ),
+            InspectorRow::Controller => format!(
+                "Controller: {}",
+                match controller.map(|controller| &controller.controller_type) {
+                    None => "None",
+                    Some(ControllerType::Player) => "Player",
+                    Some(ControllerType::Cat) => "Cat",
+                    Some(ControllerType::Dog { .. }) => "Dog",
+                    Some(ControllerType::Mouse) => "Mouse",
+                }
+            ),
+            InspectorRow::ViewRadius => format!(
+                "View radius: < {} >",
+                controller
+                    .and_then(|controller| controller.view_radius)
+                    .unwrap_or(VIEW_RADIUS)
+            ),
+            InspectorRow::Chain => format!("Chain: {}", if chain.is_some() { "on" } else { "off" }),
+            InspectorRow::ChainDistance => format!(
+                "Chain distance: < {} >",
+                chain.map(|chain| chain.distance).unwrap_or(0)
+            ),
+            InspectorRow::ChainOrigin => {
+                if self.picking_origin {
+                    "Click a tile to set the origin".to_owned()
+                } else {
+                    let origin = chain.map(|chain| chain.origin).unwrap_or(entity.position);
+                    format!("Chain origin: ({}, {})", origin.x, origin.y)
+                }
+            }
+        }
+    }
+    pub fn draw(
+        &mut self,
+        geng: &Rc<Geng>,
+        level: &Level,
+        camera: &Camera,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let entity = match self.selected.and_then(|id| level.entities.get(&id)) {
+            Some(entity) => entity,
+            None => {
+                self.selected = None;
+                return;
+            }
+        };
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        let tile_aabb = |position: Vec2<i32>| {
+            let position = position.map(|x| x as f32);
+            AABB::from_corners(
+                camera.world_to_screen(framebuffer_size, position),
+                camera.world_to_screen(framebuffer_size, position + vec2(1.0, 1.0)),
+            )
+        };
+        geng.draw_2d().quad(
+            framebuffer,
+            tile_aabb(entity.position),
+            Color::rgba(1.0, 1.0, 0.0, 0.4),
+        );
+        if let Some(EntityController {
+            controller_type:
+                ControllerType::Dog {
+                    chain: Some(Chain { origin, distance }),
+                },
+            ..
+        }) = &entity.controller
+        {
+            for dx in -distance..=*distance {
+                for dy in -distance..=*distance {
+                    let position = *origin + vec2(dx, dy);
+                    if position_distance(position, *origin) <= *distance {
+                        geng.draw_2d().quad(
+                            framebuffer,
+                            tile_aabb(position),
+                            Color::rgba(1.0, 0.0, 0.0, 0.15),
+                        );
+                    }
+                }
+            }
+        }
+
+        let rows = Self::rows(entity);
+        for (idx, &row) in rows.iter().enumerate() {
+            let aabb = Self::row_aabb(framebuffer_size, rows.len(), idx);
+            geng.draw_2d()
+                .quad(framebuffer, aabb, Color::rgba(1.0, 1.0, 1.0, 0.8));
+            geng.default_font().draw(
+                framebuffer,
+                &self.row_text(row, entity),
+                vec2(aabb.x_min + 4.0, aabb.y_min + 4.0),
+                ROW_HEIGHT - 8.0,
+                Color::BLACK,
+            );
+        }
+    }
+    /// Returns `true` if the click was consumed by the inspector.
+    pub fn handle_click(
+        &mut self,
+        level: &mut Level,
+        framebuffer_size: Vec2<f32>,
+        position: Vec2<f32>,
+        tile: Vec2<i32>,
+    ) -> bool {
+        let entity = match self.selected.and_then(|id| level.entities.get_mut(&id)) {
+            Some(entity) => entity,
+            None => return false,
+        };
+        let rows = Self::rows(entity);
+        let clicked = rows
+            .iter()
+            .enumerate()
+            .find(|&(idx, _)| Self::row_aabb(framebuffer_size, rows.len(), idx).contains(position))
+            .map(|(idx, &row)| (Self::row_aabb(framebuffer_size, rows.len(), idx), row));
+        let (aabb, row) = match clicked {
+            Some(clicked) => clicked,
+            None => {
+                if self.picking_origin {
+                    self.picking_origin = false;
+                    if let Some(EntityController {
+                        controller_type: ControllerType::Dog { chain: Some(chain) },
+                        ..
+                    }) = &mut entity.controller
+                    {
+                        chain.origin = tile;
+                    }
+                    return true;
+                }
+                return false;
+            }
+        };
+        let delta = if position.x < aabb.center().x { -1 } else { 1 };
+        let entity_type = entity.entity_type;
+        let position = entity.position;
+        match row {
+            InspectorRow::EntityType => {}
+            InspectorRow::Controller => {
+                entity.controller = next_controller(entity_type, entity.controller.take());
+            }
+            InspectorRow::ViewRadius => {
+                if let Some(controller) = &mut entity.controller {
+                    let view_radius = controller.view_radius.unwrap_or(VIEW_RADIUS);
+                    controller.view_radius = Some((view_radius + delta).max(1));
+                }
+            }
+            InspectorRow::Chain => {
+                if let Some(EntityController {
+                    controller_type: ControllerType::Dog { chain },
+                    ..
+                }) = &mut entity.controller
+                {
+                    *chain = match chain {
+                        Some(_) => None,
+                        None => Some(Chain {
+                            origin: position,
+                            distance: 2,
+                        }),
+                    };
+                }
+            }
+            InspectorRow::ChainDistance => {
+                if let Some(EntityController {
+                    controller_type: ControllerType::Dog { chain: Some(chain) },
+                    ..
+                }) = &mut entity.controller
+                {
+                    chain.distance = (chain.distance + delta).max(0);
+                }
+            }
+            InspectorRow::ChainOrigin => self.picking_origin = true,
+        }
+        true
+    }
+}
+
+/// Cycles through `None -> Player -> Cat -> Dog -> Mouse -> None`.
+fn next_controller(
+    entity_type: EntityType,
+    controller: Option<EntityController>,
+) -> Option<EntityController> {
+    let controller_type = match controller.map(|controller| controller.controller_type) {
+        None => Some(ControllerType::Player),
+        Some(ControllerType::Player) => Some(ControllerType::Cat),
+        Some(ControllerType::Cat) => Some(ControllerType::Dog { chain: None }),
+        Some(ControllerType::Dog { .. }) => Some(ControllerType::Mouse),
+        Some(ControllerType::Mouse) => None,
+    };
+    controller_type.map(|controller_type| EntityController {
+        controller_type,
+        ..EntityController::from_entity_type(entity_type).unwrap_or_else(EntityController::player)
+    })
+}
@@ -0,0 +1,243 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, IntoEnumIterator)]
+pub enum MetadataField {
+    Name,
+    Author,
+    Hint,
+    Par,
+}
+
+impl MetadataField {
+    fn label(self) -> &'static str {
+        match self {
+            MetadataField::Name => "Name",
+            MetadataField::Author => "Author",
+            MetadataField::Hint => "Hint",
+            MetadataField::Par => "Par",
+        }
+    }
+    fn get(self, level: &Level) -> String {
+        match self {
+            MetadataField::Name => level.name.clone().unwrap_or_default(),
+            MetadataField::Author => level.author.clone().unwrap_or_default(),
+            MetadataField::Hint => level.hint.clone().unwrap_or_default(),
+            MetadataField::Par => level.par.map(|par| par.to_string()).unwrap_or_default(),
+        }
+    }
+    /// Surrounding spaces are dropped and an empty value clears the field.
+    fn set(self, level: &mut Level, value: &str) {
+        let value = value.trim();
+        let value = if value.is_empty() {
+            None
+        } else {
+            Some(value.to_owned())
+        };
+        match self {
+            MetadataField::Name => level.name = value,
+            MetadataField::Author => level.author = value,
+            MetadataField::Hint => level.hint = value,
+            MetadataField::Par => level.par = value.and_then(|value| value.parse().ok()),
+        }
+    }
+    fn accepts(self, c: char) -> bool {
+        match self {
+            MetadataField::Par => c.is_ascii_digit(),
+            _ => true,
+        }
+    }
+}
+
+const ROW_HEIGHT: f32 = 32.0;
+const PANEL_WIDTH: f32 = 480.0;
+
+pub struct MetadataPanel {
+    pub open: bool,
+    /// Field being typed into and its text, which is only stored in the level
+    /// once editing stops, so that spaces can be typed anywhere.
+    editing: Option<(MetadataField, String)>,
+}
+
+impl MetadataPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            editing: None,
+        }
+    }
+    pub fn toggle(&mut self, level: &mut Level) {
+        self.open = !self.open;
+        self.select(level, None);
+    }
+    /// Stores the field being edited, then starts editing `field`.
+    fn select(&mut self, level: &mut Level, field: Option<MetadataField>) {
+        if let Some((field, value)) = self.editing.take() {
+            field.set(level, &value);
+        }
+        self.editing = field.map(|field| (field, field.get(level)));
+    }
+    fn panel_aabb(framebuffer_size: Vec2<f32>) -> AABB<f32> {
+        let rows = MetadataField::into_enum_iter().count() as f32;
+        AABB::pos_size(
+            vec2(
+                framebuffer_size.x - PANEL_WIDTH,
+                framebuffer_size.y - rows * ROW_HEIGHT,
+            ),
+            vec2(PANEL_WIDTH, rows * ROW_HEIGHT),
+        )
+    }
+    fn row_aabb(framebuffer_size: Vec2<f32>, idx: usize) -> AABB<f32> {
+        AABB::pos_size(
+            vec2(
+                framebuffer_size.x - PANEL_WIDTH,
+                framebuffer_size.y - (idx + 1) as f32 * ROW_HEIGHT,
+            ),
+            vec2(PANEL_WIDTH, ROW_HEIGHT),
+        )
+    }
+    pub fn draw(&self, geng: &Rc<Geng>, level: &Level, framebuffer: &mut ugli::Framebuffer) {
+        if !self.open {
+            return;
+        }
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        geng.draw_2d().quad(
+            framebuffer,
+            Self::panel_aabb(framebuffer_size),
+            Color::rgba(1.0, 1.0, 1.0, 0.8),
+        );
+        for (idx, field) in MetadataField::into_enum_iter().enumerate() {
+            let aabb = Self::row_aabb(framebuffer_size, idx);
+            let editing = match &self.editing {
+                Some((editing, value)) if *editing == field => Some(value),
+                _ => None,
+            };
+            if editing.is_some() {
+                geng.draw_2d()
+                    .quad(framebuffer, aabb, Color::rgba(1.0, 1.0, 0.0, 0.5));
+            }
+            geng.default_font().draw(
+                framebuffer,
+                &format!(
+                    "{}: {}",
+                    field.label(),
+                    match editing {
+                        Some(value) => format!("{}_", value),
+                        None => field.get(level),
+                    }
+                ),
+                vec2(aabb.x_min + 4.0, aabb.y_min + 4.0),
+                ROW_HEIGHT - 8.0,
+                Color::BLACK,
+            );
+        }
+    }
+    /// Returns `true` if the event was consumed by the panel.
+    pub fn handle_event(
+        &mut self,
+        geng: &Rc<Geng>,
+        level: &mut Level,
+        framebuffer_size: Vec2<f32>,
+        event: &geng::Event,
+    ) -> bool {
+        if !self.open {
+            return false;
+        }
+        match *event {
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Left,
+            } => {
+                let position = position.map(|x| x as f32);
+                if !Self::panel_aabb(framebuffer_size).contains(position) {
+                    self.select(level, None);
+                    return false;
+                }
+                let field = MetadataField::into_enum_iter()
+                    .enumerate()
+                    .find(|&(idx, _)| Self::row_aabb(framebuffer_size, idx).contains(position))
+                    .map(|(_, field)| field);
+                self.select(level, field);
+                true
+            }
+            geng::Event::KeyDown { key } => {
+                let field = match &self.editing {
+                    Some((field, _)) => *field,
+                    None => return false,
+                };
+                let fields: Vec<_> = MetadataField::into_enum_iter().collect();
+                let idx = fields.iter().position(|&other| other == field).unwrap();
+                match key {
+                    geng::Key::Enter | geng::Key::Escape => self.select(level, None),
+                    geng::Key::Up => {
+                        let field = fields[(idx + fields.len() - 1) % fields.len()];
+                        self.select(level, Some(field));
+                    }
+                    geng::Key::Down => self.select(level, Some(fields[(idx + 1) % fields.len()])),
+                    geng::Key::Backspace => {
+                        if let Some((_, value)) = &mut self.editing {
+                            value.pop();
+                        }
+                    }
+                    _ => {
+                        let shift = geng.window().is_key_pressed(geng::Key::LShift)
+                            || geng.window().is_key_pressed(geng::Key::RShift);
+                        if let Some(c) = key_char(key, shift) {
+                            if let Some((_, value)) = &mut self.editing {
+                                if field.accepts(c) {
+                                    value.push(c);
+                                }
+                            }
+                        }
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn key_char(key: geng::Key, shift: bool) -> Option<char> {
+    use geng::Key::*;
+    let c = match key {
+        A => 'a',
+        B => 'b',
+        C => 'c',
+        D => 'd',
+        E => 'e',
+        F => 'f',
+        G => 'g',
+        H => 'h',
+        I => 'i',
+        J => 'j',
+        K => 'k',
+        L => 'l',
+        M => 'm',
+        N => 'n',
+        O => 'o',
+        P => 'p',
+        Q => 'q',
+        R => 'r',
+        S => 's',
+        T => 't',
+        U => 'u',
+        V => 'v',
+        W => 'w',
+        X => 'x',
+        Y => 'y',
+        Z => 'z',
+        Num0 => '0',
+        Num1 => '1',
+        Num2 => '2',
+        Num3 => '3',
+        Num4 => '4',
+        Num5 => '5',
+        Num6 => '6',
+        Num7 => '7',
+        Num8 => '8',
+        Num9 => '9',
+        Space => ' ',
+        _ => return None,
+    };
+    Some(if shift { c.to_ascii_uppercase() } else { c })
+}
@@ -0,0 +1,390 @@
+use geng::State;
+
+use super::*;
+
+mod inspector;
+mod metadata;
+mod palette;
+mod selection;
+
+use inspector::*;
+use metadata::*;
+use palette::*;
+use selection::*;
+
+pub struct Editor {
+    geng: Rc<Geng>,
+    assets: Rc<Assets>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    progress: Rc<RefCell<Progress>>,
+    camera: Camera,
+    transition: Option<geng::Transition>,
+    selected_entity: Option<EntityType>,
+    selected_terrain: Terrain,
+    layer: Layer,
+    level: Level,
+    level_renderer: LevelRenderer,
+    framebuffer_size: Vec2<usize>,
+    metadata: MetadataPanel,
+    inspector: Inspector,
+    playtest_solution: Rc<RefCell<Option<Vec<Move>>>>,
+    save_solution: bool,
+    status: Option<String>,
+    selection: Option<Selection>,
+    selection_start: Option<Vec2<i32>>,
+    clipboard: Option<Clipboard>,
+}
+
+impl Editor {
+    pub fn new(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+    ) -> Self {
+        let mut camera = Camera::new(10.0);
+        camera.mode = CameraMode::FitLevel;
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            progress: progress.clone(),
+            camera,
+            transition: None,
+            selected_entity: None,
+            selected_terrain: Terrain::Water,
+            layer: Layer::Entities,
+            level: Level::empty(),
+            level_renderer: LevelRenderer::new(geng, assets),
+            framebuffer_size: vec2(1, 1),
+            metadata: MetadataPanel::new(),
+            inspector: Inspector::new(),
+            playtest_solution: Rc::new(RefCell::new(None)),
+            save_solution: false,
+            status: None,
+            selection: None,
+            selection_start: None,
+            clipboard: None,
+        }
+    }
+    fn mouse_tile(&self, mouse_position: Vec2<f64>) -> Vec2<i32> {
+        tile_pos(self.camera.screen_to_world(
+            self.framebuffer_size.map(|x| x as f32),
+            mouse_position.map(|x| x as f32),
+        ))
+    }
+    fn spawn_selected(&mut self, mouse_position: Vec2<f64>, spawn_player: bool) {
+        let tile_pos = self.mouse_tile(mouse_position);
+        if self.layer == Layer::Terrain {
+            self.level.set_terrain(tile_pos, self.selected_terrain);
+            return;
+        }
+        match self.selected_entity.clone() {
+            Some(selected_entity) => {
+                self.level.set_entity(Entity {
+                    position: tile_pos,
+                    render_pos: tile_pos.map(|x| x as f32),
+                    entity_type: selected_entity,
+                    controller: if spawn_player {
+                        Some(EntityController::player())
+                    } else {
+                        EntityController::from_entity_type(selected_entity)
+                    },
+                    animation: default(),
+                });
+            }
+            None => {
+                self.level.remove_entity(tile_pos);
+            }
+        }
+    }
+    fn handle_key(&mut self, key: geng::Key) {
+        match key {
+            #[cfg(not(target_arch = "wasm32"))]
+            geng::Key::S if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                batbox::save_file(
+                    "Save custom level",
+                    "levels/custom/custom_level.json",
+                    |writer| {
+                        serde_json::to_writer(writer, &self.level)?;
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            geng::Key::O if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                if let Some(path) = batbox::select_file("Load level") {
+                    let result = std::fs::File::open(path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()));
+                    match result {
+                        Ok(level) => self.level = level,
+                        Err(e) => self.status = Some(format!("Failed to load level: {}", e)),
+                    }
+                }
+            }
+            geng::Key::C if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                self.copy_selection(false)
+            }
+            geng::Key::X if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                self.copy_selection(true)
+            }
+            geng::Key::V if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                self.paste(self.mouse_tile(self.geng.window().mouse_pos()))
+            }
+            geng::Key::PageUp => self.select_delta(1),
+            geng::Key::PageDown => self.select_delta(-1),
+            _ => {
+                let action = self
+                    .settings
+                    .borrow()
+                    .bindings
+                    .key_action(key, Action::in_editor);
+                if let Some(action) = action {
+                    self.handle_action(action);
+                }
+            }
+        }
+    }
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Playtest => {
+                self.status = None;
+                self.transition = Some(geng::Transition::Push(Box::new(GameState::playtest(
+                    &self.geng,
+                    &self.assets,
+                    &self.settings,
+                    &self.gamepad,
+                    &self.progress,
+                    self.level.clone(),
+                    &self.playtest_solution,
+                ))));
+            }
+            Action::ToggleSaveSolution => self.save_solution = !self.save_solution,
+            Action::ToggleMetadata => self.metadata.toggle(&mut self.level),
+            Action::Mirror => {
+                self.mirror_selection(!self.geng.window().is_key_pressed(geng::Key::LShift))
+            }
+            Action::Rotate => self.rotate_selection(),
+            Action::ToggleIntents => {
+                let mut settings = self.settings.borrow_mut();
+                settings.show_intents = !settings.show_intents;
+                settings.save();
+            }
+            Action::Delete => self.delete_selection(),
+            Action::Deselect => self.selection = None,
+            Action::Bounds => self.edit_bounds(),
+            Action::ToggleLayer => self.layer = self.layer.toggle(),
+            _ => {
+                if let Some(direction) = action.direction() {
+                    let delta = direction.direction();
+                    if self.geng.window().is_key_pressed(geng::Key::LCtrl) {
+                        self.level.shift(delta);
+                        if let Some(selection) = &mut self.selection {
+                            selection.min += delta;
+                            selection.max += delta;
+                        }
+                    } else {
+                        self.move_selection(delta);
+                    }
+                }
+            }
+        }
+    }
+    /// With a selection, makes it the level bounds.
+    /// Otherwise cycles through the edge behaviours and then no bounds at all.
+    fn edit_bounds(&mut self) {
+        self.level.bounds = match (self.selection, self.level.bounds) {
+            (Some(selection), bounds) => Some(LevelBounds {
+                min: selection.min,
+                max: selection.max,
+                edge: bounds.map_or(EdgeBehaviour::Solid, |bounds| bounds.edge),
+            }),
+            (None, Some(bounds)) => {
+                let edges: Vec<EdgeBehaviour> = EdgeBehaviour::into_enum_iter().collect();
+                let idx = edges.iter().position(|&edge| edge == bounds.edge).unwrap();
+                edges
+                    .get(idx + 1)
+                    .map(|&edge| LevelBounds { edge, ..bounds })
+            }
+            (None, None) => None,
+        };
+        self.status = Some(match &self.level.bounds {
+            Some(bounds) => format!("Level bounds: {} edges", bounds.edge.name()),
+            None => "Level bounds: none, select an area to set them".to_owned(),
+        });
+    }
+    fn select_delta(&mut self, delta: i32) {
+        match self.layer {
+            Layer::Entities => {
+                let options = palette();
+                let idx = options
+                    .iter()
+                    .position(|&option| option == self.selected_entity)
+                    .unwrap_or(0);
+                self.selected_entity =
+                    options[(idx as i32 + delta).rem_euclid(options.len() as i32) as usize];
+            }
+            Layer::Terrain => {
+                let options = terrain_palette();
+                let idx = options
+                    .iter()
+                    .position(|&option| option == self.selected_terrain)
+                    .unwrap_or(0);
+                self.selected_terrain =
+                    options[(idx as i32 + delta).rem_euclid(options.len() as i32) as usize];
+            }
+        }
+    }
+}
+
+impl geng::State for Editor {
+    fn update(&mut self, delta_time: f64) {
+        // The editor is keyboard and mouse only, but buttons pressed here shouldn't pile up
+        // and fire once back in the game
+        self.gamepad.borrow_mut().poll();
+        if let Some(solution) = self.playtest_solution.borrow_mut().take() {
+            self.status = Some(if self.save_solution {
+                let status = format!("Solved in {} moves, solution saved", solution.len());
+                self.level.solution = Some(solution);
+                status
+            } else {
+                format!("Solved in {} moves", solution.len())
+            });
+        }
+        for entity in self.level.entities.values_mut() {
+            entity.render_pos = entity.position.map(|x| x as f32);
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.framebuffer_size = framebuffer.size();
+        self.camera
+            .update_target(&self.level, self.framebuffer_size.map(|x| x as f32));
+        self.camera.snap();
+        self.level_renderer
+            .draw(&self.level, &self.camera, framebuffer);
+        if self.settings.borrow().show_intents {
+            self.level_renderer
+                .draw_intents(&self.level, &self.camera, framebuffer);
+        }
+        self.geng.default_font().draw(
+            framebuffer,
+            &format!(
+                "Save solution on win ({}): {}",
+                self.settings
+                    .borrow()
+                    .bindings
+                    .action_name(Action::ToggleSaveSolution),
+                if self.save_solution { "on" } else { "off" }
+            ),
+            vec2(0.0, self.framebuffer_size.y as f32 - 32.0),
+            24.0,
+            Color::BLACK,
+        );
+        if let Some(status) = &self.status {
+            self.geng.default_font().draw(
+                framebuffer,
+                status,
+                vec2(0.0, self.framebuffer_size.y as f32 - 64.0),
+                24.0,
+                Color::BLACK,
+            );
+        }
+        self.geng.default_font().draw(
+            framebuffer,
+            &match self.layer {
+                Layer::Entities => format!("Selected: {}", palette_name(self.selected_entity)),
+                Layer::Terrain => format!(
+                    "Selected terrain ({}): {}",
+                    self.settings
+                        .borrow()
+                        .bindings
+                        .action_name(Action::ToggleLayer),
+                    self.selected_terrain.name()
+                ),
+            },
+            vec2(0.0, self.framebuffer_size.y as f32 - 96.0),
+            24.0,
+            Color::BLACK,
+        );
+        self.draw_palette(framebuffer);
+        self.draw_selection(framebuffer);
+        self.inspector
+            .draw(&self.geng, &self.level, &self.camera, framebuffer);
+        self.metadata.draw(&self.geng, &self.level, framebuffer);
+    }
+    fn handle_event(&mut self, event: geng::Event) {
+        if self.metadata.handle_event(
+            &self.geng,
+            &mut self.level,
+            self.framebuffer_size.map(|x| x as f32),
+            &event,
+        ) {
+            return;
+        }
+        match event {
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Left,
+            } => {
+                if self.palette_click(position.map(|x| x as f32)) {
+                    return;
+                }
+                if self.inspector.handle_click(
+                    &mut self.level,
+                    self.framebuffer_size.map(|x| x as f32),
+                    position.map(|x| x as f32),
+                    self.mouse_tile(position),
+                ) {
+                    return;
+                }
+                if self.geng.window().is_key_pressed(geng::Key::LShift) {
+                    self.selection_start = Some(self.mouse_tile(position));
+                    return;
+                }
+                self.spawn_selected(position, false)
+            }
+            geng::Event::MouseUp {
+                position,
+                button: geng::MouseButton::Left,
+            } => {
+                if let Some(start) = self.selection_start.take() {
+                    self.selection =
+                        Some(Selection::from_corners(start, self.mouse_tile(position)));
+                }
+            }
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Middle,
+            } => self
+                .inspector
+                .select(&self.level, self.mouse_tile(position)),
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Right,
+            } => self.spawn_selected(position, true),
+            geng::Event::Wheel { delta } => {
+                self.select_delta(if delta > 0.0 { 1 } else { -1 });
+            }
+            geng::Event::KeyDown { key } => {
+                // Palette hotkeys go before any other use of the same key
+                let entity_type = self.settings.borrow().bindings.palette_entity(key);
+                match entity_type {
+                    Some(entity_type) if !self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                        self.selected_entity = entity_type;
+                        self.layer = Layer::Entities;
+                    }
+                    _ => self.handle_key(key),
+                }
+            }
+            _ => (),
+        }
+    }
+    fn transition(&mut self) -> Option<geng::Transition> {
+        self.transition.take()
+    }
+}
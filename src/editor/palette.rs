@@ -0,0 +1,182 @@
+use super::*;
+
+const BUTTON_SIZE: f32 = 64.0;
+const ICON_SIZE: f32 = 40.0;
+const LABEL_SIZE: f32 = 14.0;
+
+pub fn palette() -> Vec<Option<EntityType>> {
+    std::iter::once(None)
+        .chain(EntityType::into_enum_iter().map(Some))
+        .collect()
+}
+
+fn button_aabb(idx: usize) -> AABB<f32> {
+    AABB::pos_size(
+        vec2(idx as f32 * BUTTON_SIZE, 0.0),
+        vec2(BUTTON_SIZE, BUTTON_SIZE),
+    )
+}
+
+/// Which layer of the level the editor paints on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layer {
+    Entities,
+    Terrain,
+}
+
+impl Layer {
+    pub fn toggle(self) -> Self {
+        match self {
+            Layer::Entities => Layer::Terrain,
+            Layer::Terrain => Layer::Entities,
+        }
+    }
+}
+
+pub fn terrain_palette() -> Vec<Terrain> {
+    Terrain::into_enum_iter().collect()
+}
+
+impl Editor {
+    fn palette_len(&self) -> usize {
+        match self.layer {
+            Layer::Entities => palette().len(),
+            Layer::Terrain => terrain_palette().len(),
+        }
+    }
+    fn hovered_button(&self, position: Vec2<f32>) -> Option<usize> {
+        (0..self.palette_len()).find(|&idx| button_aabb(idx).contains(position))
+    }
+    /// Returns `true` if the click hit a palette button.
+    pub fn palette_click(&mut self, position: Vec2<f32>) -> bool {
+        match self.hovered_button(position) {
+            Some(idx) => {
+                match self.layer {
+                    Layer::Entities => self.selected_entity = palette()[idx],
+                    Layer::Terrain => self.selected_terrain = terrain_palette()[idx],
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    /// Label, selection state and key of the palette buttons of the current layer.
+    fn palette_buttons(&self) -> Vec<(String, bool, Option<geng::Key>)> {
+        match self.layer {
+            Layer::Entities => palette()
+                .into_iter()
+                .map(|entity_type| {
+                    (
+                        palette_name(entity_type).to_owned(),
+                        entity_type == self.selected_entity,
+                        self.settings.borrow().bindings.palette_key(entity_type),
+                    )
+                })
+                .collect(),
+            Layer::Terrain => terrain_palette()
+                .into_iter()
+                .map(|terrain| {
+                    (
+                        terrain.name().to_owned(),
+                        terrain == self.selected_terrain,
+                        None,
+                    )
+                })
+                .collect(),
+        }
+    }
+    pub fn draw_palette(&self, framebuffer: &mut ugli::Framebuffer) {
+        let font = self.geng.default_font();
+        for (idx, (name, selected, key)) in self.palette_buttons().into_iter().enumerate() {
+            let aabb = button_aabb(idx);
+            self.geng.draw_2d().quad(
+                framebuffer,
+                aabb,
+                if selected {
+                    Color::rgba(1.0, 0.8, 0.0, 0.9)
+                } else {
+                    Color::rgba(1.0, 1.0, 1.0, 0.7)
+                },
+            );
+            let icon_aabb = AABB::pos_size(
+                aabb.center() - vec2(ICON_SIZE / 2.0, ICON_SIZE / 2.0 - LABEL_SIZE / 2.0),
+                vec2(ICON_SIZE, ICON_SIZE),
+            );
+            match self.layer {
+                Layer::Entities => {
+                    if let Some(entity_type) = palette()[idx] {
+                        self.geng.draw_2d().textured_quad(
+                            framebuffer,
+                            icon_aabb,
+                            self.assets.entity(entity_type),
+                            Color::WHITE,
+                        );
+                    }
+                }
+                Layer::Terrain => match terrain_palette()[idx] {
+                    Terrain::Water => self.geng.draw_2d().textured_quad(
+                        framebuffer,
+                        icon_aabb,
+                        &self.assets.water,
+                        Color::WHITE,
+                    ),
+                    Terrain::Bridge => self.geng.draw_2d().textured_quad(
+                        framebuffer,
+                        icon_aabb,
+                        &self.assets.bridge,
+                        Color::WHITE,
+                    ),
+                    terrain => {
+                        self.geng
+                            .draw_2d()
+                            .quad(framebuffer, icon_aabb, terrain_color(terrain))
+                    }
+                },
+            }
+            font.draw(
+                framebuffer,
+                &name,
+                vec2(aabb.x_min + 2.0, aabb.y_min + 2.0),
+                LABEL_SIZE,
+                Color::BLACK,
+            );
+            if let Some(key) = key {
+                font.draw(
+                    framebuffer,
+                    &key_name(key),
+                    vec2(aabb.x_min + 2.0, aabb.y_max - LABEL_SIZE),
+                    LABEL_SIZE,
+                    Color::rgb(0.3, 0.3, 0.3),
+                );
+            }
+        }
+
+        let mouse_pos = self.geng.window().mouse_pos().map(|x| x as f32);
+        if let Some(idx) = self.hovered_button(mouse_pos) {
+            let (name, _, key) = self.palette_buttons().remove(idx);
+            let tooltip = match key {
+                Some(key) => format!("{} ({})", name, key_name(key)),
+                None => name,
+            };
+            let pos = vec2(button_aabb(idx).x_min, BUTTON_SIZE + 4.0);
+            self.geng.draw_2d().quad(
+                framebuffer,
+                AABB::pos_size(
+                    pos,
+                    vec2(
+                        tooltip.len() as f32 * LABEL_SIZE * 0.6 + 8.0,
+                        LABEL_SIZE * 2.0,
+                    ),
+                ),
+                Color::rgba(0.0, 0.0, 0.0, 0.8),
+            );
+            font.draw(
+                framebuffer,
+                &tooltip,
+                pos + vec2(4.0, LABEL_SIZE / 2.0),
+                LABEL_SIZE,
+                Color::WHITE,
+            );
+        }
+    }
+}
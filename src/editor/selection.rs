@@ -0,0 +1,190 @@
+use super::*;
+
+/// Inclusive rectangle of tiles.
+#[derive(Clone, Copy)]
+pub struct Selection {
+    pub min: Vec2<i32>,
+    pub max: Vec2<i32>,
+}
+
+impl Selection {
+    pub fn from_corners(a: Vec2<i32>, b: Vec2<i32>) -> Self {
+        Self {
+            min: vec2(a.x.min(b.x), a.y.min(b.y)),
+            max: vec2(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+    pub fn size(&self) -> Vec2<i32> {
+        self.max - self.min + vec2(1, 1)
+    }
+}
+
+pub struct Clipboard {
+    size: Vec2<i32>,
+    /// Entities with positions relative to the copied selection.
+    entities: Vec<Entity>,
+    terrain: Vec<(Vec2<i32>, Terrain)>,
+}
+
+impl Editor {
+    fn take_selected(&mut self, selection: Selection) -> Vec<Entity> {
+        self.level
+            .entities_in(selection.min, selection.max)
+            .into_iter()
+            .filter_map(|id| self.level.entities.remove(&id))
+            .collect()
+    }
+    fn selected_terrain(&self, selection: Selection) -> Vec<(Vec2<i32>, Terrain)> {
+        self.level
+            .terrain
+            .iter()
+            .filter(|(pos, _)| {
+                selection.min.x <= pos.x
+                    && pos.x <= selection.max.x
+                    && selection.min.y <= pos.y
+                    && pos.y <= selection.max.y
+            })
+            .map(|(&pos, &terrain)| (pos, terrain))
+            .collect()
+    }
+    fn take_selected_terrain(&mut self, selection: Selection) -> Vec<(Vec2<i32>, Terrain)> {
+        let terrain = self.selected_terrain(selection);
+        for (pos, _) in &terrain {
+            self.level.terrain.remove(pos);
+        }
+        terrain
+    }
+    fn transform_selection(
+        &mut self,
+        new_size: Vec2<i32>,
+        f: impl Fn(Vec2<i32>, Vec2<i32>) -> Vec2<i32>,
+    ) {
+        if let Some(selection) = self.selection {
+            let size = selection.size();
+            for mut entity in self.take_selected(selection) {
+                entity.transform(|pos| selection.min + f(pos - selection.min, size));
+                self.level.set_entity(entity);
+            }
+            for (pos, terrain) in self.take_selected_terrain(selection) {
+                self.level
+                    .set_terrain(selection.min + f(pos - selection.min, size), terrain);
+            }
+            self.selection = Some(Selection {
+                min: selection.min,
+                max: selection.min + new_size - vec2(1, 1),
+            });
+        }
+    }
+    pub fn move_selection(&mut self, delta: Vec2<i32>) {
+        if let Some(selection) = self.selection {
+            for mut entity in self.take_selected(selection) {
+                entity.transform(|pos| pos + delta);
+                self.level.set_entity(entity);
+            }
+            for (pos, terrain) in self.take_selected_terrain(selection) {
+                self.level.set_terrain(pos + delta, terrain);
+            }
+            self.selection = Some(Selection {
+                min: selection.min + delta,
+                max: selection.max + delta,
+            });
+        }
+    }
+    pub fn mirror_selection(&mut self, horizontal: bool) {
+        if let Some(selection) = self.selection {
+            self.transform_selection(selection.size(), |pos, size| {
+                if horizontal {
+                    vec2(size.x - 1 - pos.x, pos.y)
+                } else {
+                    vec2(pos.x, size.y - 1 - pos.y)
+                }
+            });
+        }
+    }
+    /// Rotates the selection clockwise around its bottom left corner.
+    pub fn rotate_selection(&mut self) {
+        if let Some(selection) = self.selection {
+            let size = selection.size();
+            self.transform_selection(vec2(size.y, size.x), |pos, size| {
+                vec2(pos.y, size.x - 1 - pos.x)
+            });
+        }
+    }
+    pub fn copy_selection(&mut self, cut: bool) {
+        if let Some(selection) = self.selection {
+            let (entities, terrain) = if cut {
+                (
+                    self.take_selected(selection),
+                    self.take_selected_terrain(selection),
+                )
+            } else {
+                (
+                    self.level
+                        .entities_in(selection.min, selection.max)
+                        .into_iter()
+                        .map(|id| self.level.entities[&id].clone())
+                        .collect(),
+                    self.selected_terrain(selection),
+                )
+            };
+            self.clipboard = Some(Clipboard {
+                size: selection.size(),
+                entities: entities
+                    .into_iter()
+                    .map(|mut entity| {
+                        entity.transform(|pos| pos - selection.min);
+                        entity
+                    })
+                    .collect(),
+                terrain: terrain
+                    .into_iter()
+                    .map(|(pos, terrain)| (pos - selection.min, terrain))
+                    .collect(),
+            });
+        }
+    }
+    pub fn paste(&mut self, position: Vec2<i32>) {
+        if let Some(clipboard) = &self.clipboard {
+            for entity in &clipboard.entities {
+                let mut entity = entity.clone();
+                entity.transform(|pos| pos + position);
+                self.level.set_entity(entity);
+            }
+            for &(pos, terrain) in &clipboard.terrain {
+                self.level.set_terrain(pos + position, terrain);
+            }
+            self.selection = Some(Selection {
+                min: position,
+                max: position + clipboard.size - vec2(1, 1),
+            });
+        }
+    }
+    pub fn delete_selection(&mut self) {
+        if let Some(selection) = self.selection.take() {
+            self.take_selected(selection);
+            self.take_selected_terrain(selection);
+        }
+    }
+    pub fn draw_selection(&self, framebuffer: &mut ugli::Framebuffer) {
+        let selection = match (self.selection_start, self.selection) {
+            (Some(start), _) => {
+                Selection::from_corners(start, self.mouse_tile(self.geng.window().mouse_pos()))
+            }
+            (None, Some(selection)) => selection,
+            (None, None) => return,
+        };
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        self.geng.draw_2d().quad(
+            framebuffer,
+            AABB::from_corners(
+                self.camera
+                    .world_to_screen(framebuffer_size, selection.min.map(|x| x as f32)),
+                self.camera.world_to_screen(
+                    framebuffer_size,
+                    (selection.max + vec2(1, 1)).map(|x| x as f32),
+                ),
+            ),
+            Color::rgba(0.0, 0.5, 1.0, 0.3),
+        );
+    }
+}
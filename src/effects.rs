@@ -0,0 +1,139 @@
+use super::*;
+
+const DYING_TIME: f32 = 0.5;
+const PARTICLE_TIME: f32 = 0.6;
+const PARTICLES_PER_DEATH: usize = 8;
+
+struct Dying {
+    entity: Entity,
+    timer: f32,
+}
+
+struct Particle {
+    position: Vec2<f32>,
+    velocity: Vec2<f32>,
+    color: Color<f32>,
+    timer: f32,
+}
+
+/// Render-only leftovers of entities removed from the level.
+pub struct Effects {
+    dying: Vec<Dying>,
+    particles: Vec<Particle>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            dying: Vec::new(),
+            particles: Vec::new(),
+        }
+    }
+    pub fn handle_events(&mut self, events: &[TurnEvent]) {
+        for event in events {
+            match event {
+                TurnEvent::Eaten { entity, .. } => {
+                    let center = entity.position.map(|x| x as f32) + vec2(0.5, 0.5);
+                    for i in 0..PARTICLES_PER_DEATH {
+                        let angle =
+                            i as f32 / PARTICLES_PER_DEATH as f32 * 2.0 * std::f32::consts::PI;
+                        let speed = if i % 2 == 0 { 2.0 } else { 1.2 };
+                        self.particles.push(Particle {
+                            position: center,
+                            velocity: vec2(angle.cos(), angle.sin()) * speed,
+                            color: entity_color(entity.entity_type),
+                            timer: PARTICLE_TIME,
+                        });
+                    }
+                    self.dying.push(Dying {
+                        entity: entity.clone(),
+                        timer: DYING_TIME,
+                    });
+                }
+                TurnEvent::Sunk { entity } => {
+                    let center = entity.position.map(|x| x as f32) + vec2(0.5, 0.5);
+                    for i in 0..PARTICLES_PER_DEATH {
+                        let angle =
+                            i as f32 / PARTICLES_PER_DEATH as f32 * 2.0 * std::f32::consts::PI;
+                        self.particles.push(Particle {
+                            position: center,
+                            velocity: vec2(angle.cos(), angle.sin()) * 1.5,
+                            color: Color::rgb(0.6, 0.8, 1.0),
+                            timer: PARTICLE_TIME,
+                        });
+                    }
+                    self.dying.push(Dying {
+                        entity: entity.clone(),
+                        timer: DYING_TIME,
+                    });
+                }
+                TurnEvent::LeftLevel { entity } => {
+                    self.dying.push(Dying {
+                        entity: entity.clone(),
+                        timer: DYING_TIME,
+                    });
+                }
+            }
+        }
+    }
+    pub fn update(&mut self, delta_time: f32) {
+        for dying in &mut self.dying {
+            dying.timer -= delta_time;
+            let entity = &mut dying.entity;
+            entity.render_pos +=
+                (entity.position.map(|x| x as f32) - entity.render_pos).clamp(delta_time * 10.0);
+        }
+        self.dying.retain(|dying| dying.timer > 0.0);
+        for particle in &mut self.particles {
+            particle.timer -= delta_time;
+            particle.position += particle.velocity * delta_time;
+            particle.velocity = particle.velocity * (1.0 - (delta_time * 3.0).min(1.0));
+        }
+        self.particles.retain(|particle| particle.timer > 0.0);
+    }
+    pub fn draw<'a>(
+        &self,
+        assets: &'a Assets,
+        white: &'a ugli::Texture,
+        batch: &mut SpriteBatch<'a>,
+    ) {
+        for dying in &self.dying {
+            let t = 1.0 - dying.timer / DYING_TIME;
+            // Quick squash and stretch before shrinking away
+            let chomp = (t * 3.0 * std::f32::consts::PI).sin().abs() * 0.3 * (1.0 - t);
+            let scale = vec2(1.0 + chomp, 1.0 - chomp) * (1.0 - t);
+            batch.add(
+                Mat4::translate((dying.entity.render_pos + vec2(0.5, 0.0)).extend(0.0))
+                    * Mat4::scale(scale.extend(1.0))
+                    * Mat4::translate(vec3(-0.5, 0.0, 0.0)),
+                Mat4::identity(),
+                assets.entity(dying.entity.entity_type),
+                Color::rgba(1.0, 1.0, 1.0, 1.0 - t),
+            );
+        }
+        for particle in &self.particles {
+            let size = 0.15 * particle.timer / PARTICLE_TIME;
+            batch.add(
+                Mat4::translate((particle.position - vec2(size, size) / 2.0).extend(0.0))
+                    * Mat4::scale_uniform(size),
+                Mat4::identity(),
+                white,
+                particle.color,
+            );
+        }
+    }
+}
+
+/// Main color of the sprite, for particles and overlays.
+pub fn entity_color(entity_type: EntityType) -> Color<f32> {
+    use EntityType::*;
+    match entity_type {
+        Cat => Color::rgb(0.97, 0.46, 0.13),
+        Dog => Color::rgb(0.5, 0.3, 0.0),
+        Mouse => Color::rgb(0.66, 0.66, 0.66),
+        Cheese => Color::rgb(1.0, 0.85, 0.2),
+        Bone => Color::rgb(0.9, 0.9, 0.85),
+        Fish => Color::rgb(0.0, 0.4, 1.0),
+        _ => Color::WHITE,
+    }
+}
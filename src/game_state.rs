@@ -1,9 +1,60 @@
 use super::*;
 
+/// Time for one step animation at normal animation speed.
+const STEP_TIME: f32 = 0.1;
+const INPUT_BUFFER_SIZE: usize = 3;
+/// Pointer travel, relative to the screen height, that counts as a swipe.
+const SWIPE_DISTANCE: f32 = 0.08;
+const AUTO_WALK_SEARCH: i32 = 1000;
+const SOLVER_MAX_STATES: usize = 20000;
+/// Turns that can be undone.
+const UNDO_LIMIT: usize = 1000;
+/// Solver states searched each frame, so that hints and dead end checks don't freeze the game.
+const SOLVER_STATES_PER_FRAME: usize = 200;
+
+struct HeldKey {
+    key: geng::Key,
+    action: Action,
+    timer: f32,
+}
+
+enum Hint {
+    Searching,
+    Move(Move),
+    Unwinnable,
+    NotFound,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DeadEndItem {
+    Undo,
+    Restart,
+    KeepPlaying,
+}
+
+const DEAD_END_ITEMS: [(DeadEndItem, &str); 3] = [
+    (DeadEndItem::Undo, "undo"),
+    (DeadEndItem::Restart, "restart"),
+    (DeadEndItem::KeepPlaying, "keep playing"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PauseItem {
+    Resume,
+    Restart,
+    LevelSelect,
+    Settings,
+    BackToEditor,
+}
+
 pub struct GameState {
     geng: Rc<Geng>,
     assets: Rc<Assets>,
-    next_level: Option<usize>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    progress: Rc<RefCell<Progress>>,
+    /// Index in the level pack, `None` for custom levels.
+    level_index: Option<usize>,
     camera: Camera,
     initial_level: Level,
     level: Level,
@@ -11,160 +62,717 @@ pub struct GameState {
     transition: Option<geng::Transition>,
     win_timer: f64,
     played_final_sound: bool,
+    moves: Vec<Move>,
+    history: History,
+    playtest: Option<Rc<RefCell<Option<Vec<Move>>>>>,
+    /// Plays back the moves of an earlier win instead of taking input.
+    replay: Option<LevelResult>,
+    time: f64,
+    pause: Option<Menu>,
+    framebuffer_size: Vec2<f32>,
+    input_queue: std::collections::VecDeque<Action>,
+    step_timer: f32,
+    held: Option<HeldKey>,
+    pointer_start: Option<Vec2<f32>>,
+    pointer_last: Vec2<f32>,
+    auto_walk: Option<Vec2<i32>>,
+    /// The level as it would be after the previewed move.
+    preview: Option<Level>,
+    hint: Option<Hint>,
+    hints_used: usize,
+    /// Soft loss prompt, shown once the level can no longer be won.
+    dead_end: Option<Menu>,
+    /// Set when the player chose to keep playing, until the next undo.
+    dead_end_dismissed: bool,
+    /// Search from the current state, shared by the hint and the dead end check,
+    /// which uses it to find unwinnable states the quick check misses when enabled in the settings.
+    solver: Option<Solver>,
 }
 
 impl GameState {
     pub fn new(
         geng: &Rc<Geng>,
         assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
         level: Level,
-        next_level: Option<usize>,
+        level_index: Option<usize>,
     ) -> Self {
         let initial_level = level.clone();
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            progress: progress.clone(),
             camera: Camera::new(10.0),
             initial_level,
             level,
             level_renderer: LevelRenderer::new(geng, assets),
             transition: None,
-            next_level,
+            level_index,
             win_timer: 1.0,
             played_final_sound: false,
+            moves: Vec::new(),
+            history: History::new(UNDO_LIMIT),
+            playtest: None,
+            replay: None,
+            time: 0.0,
+            pause: None,
+            framebuffer_size: vec2(1.0, 1.0),
+            input_queue: std::collections::VecDeque::new(),
+            step_timer: 0.0,
+            held: None,
+            pointer_start: None,
+            pointer_last: vec2(0.0, 0.0),
+            auto_walk: None,
+            preview: None,
+            hint: None,
+            hints_used: 0,
+            dead_end: None,
+            dead_end_dismissed: false,
+            solver: None,
+        }
+    }
+    /// Creates a state that pops back on escape or win.
+    /// The winning move sequence is written into `solution`.
+    pub fn playtest(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+        level: Level,
+        solution: &Rc<RefCell<Option<Vec<Move>>>>,
+    ) -> Self {
+        Self {
+            playtest: Some(solution.clone()),
+            ..Self::new(geng, assets, settings, gamepad, progress, level, None)
+        }
+    }
+    /// Plays the moves of `result` back, then returns to its results screen.
+    pub fn replay(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+        level: Level,
+        result: LevelResult,
+    ) -> Self {
+        let level_index = result.level_index;
+        Self {
+            replay: Some(result),
+            ..Self::new(
+                geng,
+                assets,
+                settings,
+                gamepad,
+                progress,
+                level,
+                Some(level_index),
+            )
+        }
+    }
+    fn pause_items(&self) -> Vec<(PauseItem, &'static str)> {
+        let mut items = vec![
+            (PauseItem::Resume, "resume"),
+            (PauseItem::Restart, "restart"),
+        ];
+        if self.playtest.is_none() {
+            items.push((PauseItem::LevelSelect, "level select"));
+        }
+        items.push((PauseItem::Settings, "settings"));
+        if self.playtest.is_some() {
+            items.push((PauseItem::BackToEditor, "back to editor"));
+        }
+        items
+    }
+    fn handle_pause_action(&mut self, action: Option<MenuAction>) {
+        let items = self.pause_items();
+        match action {
+            Some(MenuAction::Back) => self.pause = None,
+            Some(MenuAction::Select(idx)) => match items[idx].0 {
+                PauseItem::Resume => self.pause = None,
+                PauseItem::Restart => {
+                    self.transition = Some(geng::Transition::Switch(Box::new(self.restart())));
+                }
+                PauseItem::LevelSelect => self.transition = Some(geng::Transition::Pop),
+                PauseItem::Settings => {
+                    self.transition = Some(geng::Transition::Push(Box::new(SettingsScreen::new(
+                        &self.geng,
+                        &self.assets,
+                        &self.settings,
+                        &self.gamepad,
+                    ))));
+                }
+                PauseItem::BackToEditor => self.transition = Some(geng::Transition::Pop),
+            },
+            _ => {}
+        }
+    }
+    fn handle_dead_end_action(&mut self, action: Option<MenuAction>) {
+        let item = match action {
+            Some(MenuAction::Select(idx)) => DEAD_END_ITEMS[idx].0,
+            Some(MenuAction::Back) => DeadEndItem::KeepPlaying,
+            _ => return,
+        };
+        self.dead_end = None;
+        match item {
+            DeadEndItem::Undo => self.undo(),
+            DeadEndItem::Restart => {
+                self.transition = Some(geng::Transition::Switch(Box::new(self.restart())));
+            }
+            DeadEndItem::KeepPlaying => self.dead_end_dismissed = true,
+        }
+    }
+    fn check_dead_end(&mut self) {
+        self.solver = None;
+        if self.replay.is_some() || self.dead_end_dismissed {
+            return;
+        }
+        if self.level.is_dead_end() {
+            self.show_dead_end();
+        } else if self.settings.borrow().solver_dead_ends
+            && self.level.get_state() == LevelState::Playing
+        {
+            self.solver = Some(Solver::new(&self.level, SOLVER_MAX_STATES));
+        }
+    }
+    fn show_dead_end(&mut self) {
+        self.dead_end = Some(Menu::new());
+        self.input_queue.clear();
+        self.held = None;
+        self.auto_walk = None;
+    }
+    /// Advances the search by one frame's worth of states.
+    fn update_solver(&mut self) {
+        let result = match &mut self.solver {
+            Some(solver) => match solver.step(SOLVER_STATES_PER_FRAME) {
+                Some(result) => result,
+                None => return,
+            },
+            None => return,
+        };
+        let unsolvable = matches!(result, SolveResult::Unsolvable);
+        if let Some(Hint::Searching) = self.hint {
+            self.hint = Some(match result {
+                SolveResult::Solved(moves) => Hint::Move(moves[0]),
+                SolveResult::Unsolvable => Hint::Unwinnable,
+                SolveResult::Unknown => Hint::NotFound,
+            });
+        }
+        if unsolvable
+            && self.settings.borrow().solver_dead_ends
+            && !self.dead_end_dismissed
+            && self.replay.is_none()
+        {
+            self.show_dead_end();
+        }
+    }
+    /// Turns and undos are queued, so that presses made during an animation
+    /// play out one after another instead of being skipped.
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Restart => {
+                self.transition = Some(geng::Transition::Switch(Box::new(self.restart())));
+            }
+            Action::Camera => {
+                self.camera.mode = self.camera.mode.next();
+            }
+            Action::Preview => {}
+            Action::Hint => self.show_hint(),
+            Action::ToggleIntents => {
+                let mut settings = self.settings.borrow_mut();
+                settings.show_intents = !settings.show_intents;
+                settings.save();
+            }
+            Action::Pause => {
+                self.pause = Some(Menu::new());
+                self.input_queue.clear();
+                self.held = None;
+                self.auto_walk = None;
+            }
+            _ => {
+                if self.replay.is_none() && self.input_queue.len() < INPUT_BUFFER_SIZE {
+                    self.input_queue.push_back(action);
+                }
+            }
+        }
+    }
+    fn apply_action(&mut self, action: Action) {
+        self.preview = None;
+        self.hint = None;
+        let player_move = match action {
+            Action::Undo => {
+                self.undo();
+                return;
+            }
+            _ => match action.player_move() {
+                Some(player_move) => player_move,
+                None => return,
+            },
+        };
+        if self.level.get_state() == LevelState::Playing {
+            self.settings.borrow().play(&self.assets.step);
+            self.history.push(&self.level);
+            self.level.turn(player_move);
+            self.level_renderer.handle_events(&self.level.events);
+            self.moves.push(player_move);
+            self.check_dead_end();
+        }
+    }
+    /// Searches for a solution from the current state, not the initial one.
+    fn show_hint(&mut self) {
+        if self.replay.is_some()
+            || self.level.get_state() != LevelState::Playing
+            || matches!(self.hint, Some(Hint::Searching))
+        {
+            return;
+        }
+        // The dead end check may already be searching from here
+        if self.solver.is_none() {
+            self.solver = Some(Solver::new(&self.level, SOLVER_MAX_STATES));
+        }
+        self.hint = Some(Hint::Searching);
+        self.hints_used += 1;
+        if let Some(idx) = self.level_index {
+            let mut progress = self.progress.borrow_mut();
+            progress.record_hint(idx);
+            progress.save();
+        }
+    }
+    fn preview_held(&self) -> bool {
+        let window = self.geng.window();
+        self.settings
+            .borrow()
+            .bindings
+            .action_keys(Action::Preview)
+            .into_iter()
+            .any(|key| window.is_key_pressed(key))
+    }
+    /// Simulates the move on a copy of the level, without making it.
+    fn show_preview(&mut self, player_move: Move) {
+        if self.level.get_state() != LevelState::Playing {
+            return;
+        }
+        let mut level = self.level.clone();
+        level.turn(player_move);
+        self.preview = Some(level);
+    }
+    fn update_input(&mut self, delta_time: f32) {
+        if self.preview.is_some() && !self.preview_held() {
+            self.preview = None;
+        }
+        let (repeat_interval, animation_speed) = {
+            let settings = self.settings.borrow();
+            (settings.repeat_interval, settings.animation_speed)
+        };
+        if self.input_queue.is_empty() && self.step_timer <= 0.0 {
+            match &self.replay {
+                Some(replay) => {
+                    if let Some(&next_move) = replay.moves.get(self.moves.len()) {
+                        self.input_queue.push_back(Action::from_move(next_move));
+                    }
+                }
+                None => self.auto_walk_step(),
+            }
+        }
+        if let Some(held) = &mut self.held {
+            if !self.geng.window().is_key_pressed(held.key) {
+                self.held = None;
+            } else {
+                held.timer -= delta_time;
+                if held.timer <= 0.0 && self.input_queue.is_empty() {
+                    self.input_queue.push_back(held.action);
+                    held.timer += repeat_interval;
+                }
+            }
+        }
+        self.step_timer -= delta_time * animation_speed;
+        if self.step_timer <= 0.0 {
+            if let Some(action) = self.input_queue.pop_front() {
+                self.apply_action(action);
+                self.step_timer = STEP_TIME;
+            }
         }
     }
+    /// Queues the next move towards the auto-walk target,
+    /// recomputing the path every turn since animals keep moving.
+    fn auto_walk_step(&mut self) {
+        let target = match self.auto_walk {
+            Some(target) => target,
+            None => return,
+        };
+        let (player_type, player_pos) = match self.level.get_player() {
+            Some(player) if self.level.get_state() == LevelState::Playing => {
+                (player.entity_type, player.position)
+            }
+            _ => {
+                self.auto_walk = None;
+                return;
+            }
+        };
+        if player_pos == target {
+            self.auto_walk = None;
+            return;
+        }
+        let next_move = self
+            .level
+            .pathfind(player_type, player_pos, target, AUTO_WALK_SEARCH)
+            .and_then(Move::from_direction);
+        match next_move {
+            Some(next_move) => self.input_queue.push_back(Action::from_move(next_move)),
+            None => self.auto_walk = None,
+        }
+    }
+    /// A tap next to the player moves there, a tap further away walks there.
+    fn handle_tap(&mut self, position: Vec2<f32>) {
+        let player_pos = match self.level.get_player() {
+            Some(player) => player.position,
+            None => return,
+        };
+        let tile = tile_pos(self.camera.screen_to_world(self.framebuffer_size, position));
+        let delta = tile - player_pos;
+        if delta.x.abs() + delta.y.abs() <= 1 {
+            if let Some(player_move) = Move::from_direction(delta) {
+                self.handle_action(Action::from_move(player_move));
+            }
+        } else {
+            self.input_queue.clear();
+            self.auto_walk = Some(tile);
+        }
+    }
+    fn handle_pointer_down(&mut self, position: Vec2<f32>) {
+        self.auto_walk = None;
+        match hud_button_at(self.framebuffer_size, position) {
+            Some(HudButton::Undo) => self.handle_action(Action::Undo),
+            Some(HudButton::Menu) => self.handle_action(Action::Pause),
+            None => {
+                self.pointer_start = Some(position);
+                self.pointer_last = position;
+            }
+        }
+    }
+    fn handle_pointer_up(&mut self, position: Vec2<f32>) {
+        let start = match self.pointer_start.take() {
+            Some(start) => start,
+            None => return,
+        };
+        let delta = position - start;
+        if delta.len() < SWIPE_DISTANCE * self.framebuffer_size.y {
+            self.handle_tap(start);
+            return;
+        }
+        let action = if delta.x.abs() > delta.y.abs() {
+            if delta.x > 0.0 {
+                Action::MoveRight
+            } else {
+                Action::MoveLeft
+            }
+        } else if delta.y > 0.0 {
+            Action::MoveUp
+        } else {
+            Action::MoveDown
+        };
+        self.handle_action(action);
+    }
+    fn controls(&self) -> String {
+        let settings = self.settings.borrow();
+        let bindings = &settings.bindings;
+        format!(
+            "{} move, {} waits, {} undoes, {} restarts, {} camera, {} animal plans, {} hint, {} pauses, hold {} to preview a move, click or swipe to walk",
+            bindings.movement_name(),
+            bindings.action_name(Action::Wait),
+            bindings.action_name(Action::Undo),
+            bindings.action_name(Action::Restart),
+            bindings.action_name(Action::Camera),
+            bindings.action_name(Action::ToggleIntents),
+            bindings.action_name(Action::Hint),
+            bindings.action_name(Action::Pause),
+            bindings.action_name(Action::Preview),
+        )
+        .to_lowercase()
+    }
+    fn undo(&mut self) {
+        if self.level.get_state() == LevelState::Win {
+            return;
+        }
+        if self.history.undo(&mut self.level) {
+            self.hint = None;
+            self.dead_end_dismissed = false;
+            self.solver = None;
+            self.moves.pop();
+            self.played_final_sound = false;
+        }
+    }
+    fn restart(&self) -> Self {
+        Self {
+            playtest: self.playtest.clone(),
+            replay: self.replay.clone(),
+            ..Self::new(
+                &self.geng,
+                &self.assets,
+                &self.settings,
+                &self.gamepad,
+                &self.progress,
+                self.initial_level.clone(),
+                self.level_index,
+            )
+        }
+    }
+    fn finish(&mut self) {
+        if let Some(solution) = &self.playtest {
+            *solution.borrow_mut() = Some(self.moves.clone());
+            self.transition = Some(geng::Transition::Pop);
+            return;
+        }
+        // Only playtests are played without being part of the pack
+        let level_index = match self.level_index {
+            Some(idx) => idx,
+            None => {
+                self.transition = Some(geng::Transition::Pop);
+                return;
+            }
+        };
+        let (result, new_best) = match self.replay.take() {
+            Some(result) => (result, false),
+            None => {
+                let result = LevelResult::new(
+                    level_index,
+                    self.moves.clone(),
+                    self.level.par,
+                    self.time,
+                    self.hints_used,
+                );
+                let mut progress = self.progress.borrow_mut();
+                let new_best = progress.record(level_index, &result);
+                progress.save();
+                (result, new_best)
+            }
+        };
+        self.transition = Some(geng::Transition::Switch(Box::new(LevelResults::new(
+            &self.geng,
+            &self.assets,
+            &self.settings,
+            &self.gamepad,
+            &self.progress,
+            result,
+            new_best,
+        ))));
+    }
 }
 
 impl geng::State for GameState {
     fn update(&mut self, delta_time: f64) {
+        let buttons = self.gamepad.borrow_mut().poll();
+        for button in buttons {
+            let action = self.settings.borrow().bindings.button_action(button);
+            if let Some(action) = action {
+                let count = self.pause_items().len();
+                if let Some(menu) = &mut self.dead_end {
+                    let action = menu.handle_action(action, DEAD_END_ITEMS.len());
+                    self.handle_dead_end_action(action);
+                    continue;
+                }
+                match &mut self.pause {
+                    Some(menu) => {
+                        let action = menu.handle_action(action, count);
+                        self.handle_pause_action(action);
+                    }
+                    None => self.handle_action(action),
+                }
+            }
+        }
+        if self.pause.is_none() && self.dead_end.is_none() {
+            self.update_input(delta_time as f32);
+            self.update_solver();
+            if self.level.get_state() == LevelState::Playing {
+                self.time += delta_time;
+            }
+        }
         self.camera.update(delta_time as f32);
+        self.level_renderer
+            .update(delta_time as f32 * self.settings.borrow().animation_speed);
         if self.level.get_state() == LevelState::Win && self.transition.is_none() {
             self.win_timer -= delta_time;
             if self.win_timer < 0.0 {
-                let next_level = self.next_level.and_then(|next| {
-                    if next < self.assets.levels.len() {
-                        Some(next)
-                    } else {
-                        None
-                    }
-                });
-                if let Some(next) = next_level {
-                    self.transition = Some(geng::Transition::Switch(Box::new(GameState::new(
-                        &self.geng,
-                        &self.assets,
-                        self.assets.levels[next].clone(),
-                        Some(next + 1),
-                    ))));
-                } else {
-                }
+                self.finish();
             }
         }
+        let animation_speed = self.settings.borrow().animation_speed;
         for entity in self.level.entities.values_mut() {
             entity.render_pos += (entity.position.map(|x| x as f32) - entity.render_pos)
-                .clamp(delta_time as f32 * 10.0);
+                .clamp(delta_time as f32 / STEP_TIME * animation_speed);
         }
     }
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
-        if self.win_timer > 0.0 {
-            self.camera.optimize(&self.level);
+        self.framebuffer_size = framebuffer.size().map(|x| x as f32);
+        self.camera
+            .update_target(&self.level, framebuffer.size().map(|x| x as f32));
+        self.level_renderer
+            .draw(&self.level, &self.camera, framebuffer);
+        if let Some(preview) = &self.preview {
             self.level_renderer
-                .draw(&self.level, &self.camera, framebuffer);
-            let text = match self.level.get_state() {
-                LevelState::Playing => self
-                    .level
-                    .name
-                    .as_ref()
-                    .map(|name| name.as_str())
-                    .unwrap_or("custom level"),
-                LevelState::Loss => {
-                    if !self.played_final_sound {
-                        self.assets.loss.play().set_volume(0.5);
-                        self.played_final_sound = true;
-                    }
-                    "f"
+                .draw_ghosts(&self.level, preview, &self.camera, framebuffer);
+        }
+        if self.settings.borrow().show_intents {
+            self.level_renderer
+                .draw_intents(&self.level, &self.camera, framebuffer);
+        }
+        if let Some(Hint::Move(hint_move)) = self.hint {
+            self.level_renderer
+                .draw_hint(&self.level, hint_move, &self.camera, framebuffer);
+        }
+        let notice = match self.hint {
+            Some(Hint::Searching) => Some("hint: thinking...".to_owned()),
+            Some(Hint::Move(Move::Wait)) => Some("hint: wait".to_owned()),
+            Some(Hint::Move(hint_move)) => Some(format!("hint: move {}", hint_move.name())),
+            Some(Hint::Unwinnable) => Some("this state is unwinnable, undo or restart".to_owned()),
+            Some(Hint::NotFound) => {
+                Some("no solution found from here, try undoing or restarting".to_owned())
+            }
+            None => None,
+        };
+        let message = match self.level.get_state() {
+            LevelState::Playing => None,
+            LevelState::Loss => {
+                if !self.played_final_sound {
+                    self.settings.borrow().play(&self.assets.loss);
+                    self.played_final_sound = true;
                 }
-                LevelState::Win => {
-                    if !self.played_final_sound {
-                        self.assets.win.play().set_volume(0.5);
-                        self.played_final_sound = true;
-                    }
-                    "pog"
+                Some("f")
+            }
+            LevelState::Win => {
+                if !self.played_final_sound {
+                    self.settings.borrow().play(&self.assets.win);
+                    self.played_final_sound = true;
                 }
-            };
-            self.level_renderer.renderer.draw_text(
-                framebuffer,
-                &Camera::new(10.0),
-                text,
-                vec2(0.0, 4.0),
-                0.5,
-                1.0,
+                Some("pog")
+            }
+        };
+        draw_hud(
+            &self.level_renderer.renderer,
+            &self.assets.font,
+            framebuffer,
+            &HudInfo {
+                title: self.level.name.as_deref().unwrap_or("custom level"),
+                hint: self.level.hint.as_deref(),
+                moves: self.moves.len(),
+                par: self.level.par,
+                targets: self.level.remaining_targets(),
+                message,
+                notice: notice.as_deref(),
+                controls: &self.controls(),
+            },
+        );
+        if let Some(menu) = &self.pause {
+            draw_overlay(&self.geng, framebuffer);
+            let items: Vec<String> = self
+                .pause_items()
+                .into_iter()
+                .map(|(_, label)| label.to_owned())
+                .collect();
+            menu.draw(
+                &self.level_renderer.renderer,
                 &self.assets.font,
-                Color::BLACK,
-            );
-            self.level_renderer.renderer.draw_text(
                 framebuffer,
-                &Camera::new(10.0),
-                "r restarts",
-                vec2(0.0, -4.9),
-                0.5,
-                1.0,
-                &self.assets.font,
-                Color::BLACK,
+                "paused",
+                &items,
             );
-        } else {
-            ugli::clear(framebuffer, Some(Color::BLACK), None);
-            self.level_renderer.renderer.draw_text(
-                framebuffer,
-                &Camera::new(3.0),
-                "gg",
-                vec2(0.0, -1.0),
-                0.5,
-                2.0,
+        }
+        if let Some(menu) = &self.dead_end {
+            draw_overlay(&self.geng, framebuffer);
+            let items: Vec<String> = DEAD_END_ITEMS
+                .iter()
+                .map(|(_, label)| (*label).to_owned())
+                .collect();
+            menu.draw(
+                &self.level_renderer.renderer,
                 &self.assets.font,
-                Color::WHITE,
+                framebuffer,
+                "no way to win from here",
+                &items,
             );
         }
     }
     fn handle_event(&mut self, event: geng::Event) {
-        let mut player_move = None;
+        if let Some(menu) = &mut self.dead_end {
+            let action = menu.handle_event(
+                &event,
+                &self.settings.borrow().bindings,
+                self.framebuffer_size,
+                DEAD_END_ITEMS.len(),
+            );
+            self.handle_dead_end_action(action);
+            return;
+        }
+        let count = self.pause_items().len();
+        if let Some(menu) = &mut self.pause {
+            let action = menu.handle_event(
+                &event,
+                &self.settings.borrow().bindings,
+                self.framebuffer_size,
+                count,
+            );
+            self.handle_pause_action(action);
+            return;
+        }
         match event {
-            geng::Event::KeyDown { key } => match key {
-                geng::Key::Right => {
-                    player_move = Some(Move::Right);
-                }
-                geng::Key::Left => {
-                    player_move = Some(Move::Left);
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Left,
+            } => self.handle_pointer_down(position.map(|x| x as f32)),
+            geng::Event::MouseUp {
+                position,
+                button: geng::MouseButton::Left,
+            } => self.handle_pointer_up(position.map(|x| x as f32)),
+            geng::Event::TouchStart { touches } => {
+                if let Some(touch) = touches.first() {
+                    self.handle_pointer_down(touch.position.map(|x| x as f32));
                 }
-                geng::Key::Up => {
-                    player_move = Some(Move::Up);
-                }
-                geng::Key::Down => {
-                    player_move = Some(Move::Down);
+            }
+            geng::Event::TouchMove { touches } => {
+                if let Some(touch) = touches.first() {
+                    self.pointer_last = touch.position.map(|x| x as f32);
                 }
-                geng::Key::Space => {
-                    player_move = Some(Move::Wait);
+            }
+            geng::Event::TouchEnd => self.handle_pointer_up(self.pointer_last),
+            geng::Event::KeyDown { key } => {
+                self.auto_walk = None;
+                // Repeated key down events from the OS are replaced by our own auto-repeat
+                if self.held.as_ref().map(|held| held.key) == Some(key) {
+                    return;
                 }
-                geng::Key::R => {
-                    self.transition = Some(geng::Transition::Switch(Box::new(GameState::new(
-                        &self.geng,
-                        &self.assets,
-                        self.initial_level.clone(),
-                        self.next_level,
-                    ))));
+                let action = self
+                    .settings
+                    .borrow()
+                    .bindings
+                    .key_action(key, Action::in_game);
+                if let Some(action) = action {
+                    if self.preview_held() {
+                        if let Some(player_move) = action.player_move() {
+                            self.show_preview(player_move);
+                            return;
+                        }
+                    }
+                    if action.direction().is_some() {
+                        self.held = Some(HeldKey {
+                            key,
+                            action,
+                            timer: self.settings.borrow().repeat_delay,
+                        });
+                    }
+                    self.handle_action(action);
                 }
-                geng::Key::Escape => {
-                    self.transition = Some(geng::Transition::Pop);
+            }
+            geng::Event::KeyUp { key } => {
+                if self.held.as_ref().map(|held| held.key) == Some(key) {
+                    self.held = None;
                 }
-                _ => (),
-            },
-            _ => {}
-        }
-        if let Some(player_move) = player_move {
-            if self.level.get_state() == LevelState::Playing {
-                self.assets.step.play().set_volume(0.5);
-                self.level.turn(player_move);
             }
+            _ => {}
         }
     }
     fn transition(&mut self) -> Option<geng::Transition> {
@@ -0,0 +1,182 @@
+use super::*;
+
+const HUD_FOV: f32 = 10.0;
+const TEXT_SIZE: f32 = 0.4;
+const TITLE_SIZE: f32 = 0.6;
+const MARGIN: f32 = 0.3;
+
+pub struct HudInfo<'a> {
+    pub title: &'a str,
+    pub hint: Option<&'a str>,
+    pub moves: usize,
+    pub par: Option<usize>,
+    pub targets: Vec<(EntityType, usize)>,
+    pub message: Option<&'a str>,
+    /// Smaller text under the message, like hints.
+    pub notice: Option<&'a str>,
+    pub controls: &'a str,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HudButton {
+    Undo,
+    Menu,
+}
+
+impl HudButton {
+    fn label(self) -> &'static str {
+        match self {
+            HudButton::Undo => "[undo]",
+            HudButton::Menu => "[menu]",
+        }
+    }
+}
+
+/// Buttons for playing without a keyboard, stacked at the right edge.
+fn hud_buttons(framebuffer_size: Vec2<f32>) -> Vec<(HudButton, AABB<f32>)> {
+    let half_width = HUD_FOV / 2.0 * framebuffer_size.x / framebuffer_size.y;
+    let right = half_width - MARGIN;
+    [HudButton::Menu, HudButton::Undo]
+        .iter()
+        .enumerate()
+        .map(|(idx, &button)| {
+            let width = measure_text(button.label(), TEXT_SIZE).x;
+            let y = TEXT_SIZE - idx as f32 * TEXT_SIZE * 2.0;
+            let aabb = AABB::from_corners(vec2(right - width, y), vec2(right, y + TEXT_SIZE));
+            (button, aabb)
+        })
+        .collect()
+}
+
+pub fn hud_button_at(framebuffer_size: Vec2<f32>, position: Vec2<f32>) -> Option<HudButton> {
+    let position = Camera::new(HUD_FOV).screen_to_world(framebuffer_size, position);
+    hud_buttons(framebuffer_size)
+        .into_iter()
+        .find(|(_, aabb)| aabb.contains(position))
+        .map(|(button, _)| button)
+}
+
+/// Draws the HUD anchored to the screen corners, so it fits any aspect ratio.
+pub fn draw_hud(
+    renderer: &Renderer,
+    font: &ugli::Texture,
+    framebuffer: &mut ugli::Framebuffer,
+    info: &HudInfo,
+) {
+    let camera = Camera::new(HUD_FOV);
+    let framebuffer_size = framebuffer.size().map(|x| x as f32);
+    let half_height = HUD_FOV / 2.0;
+    let half_width = half_height * framebuffer_size.x / framebuffer_size.y;
+    let left = -half_width + MARGIN;
+    let right = half_width - MARGIN;
+    let top = half_height - MARGIN;
+    let column_width = half_width - MARGIN * 2.0;
+
+    let mut text = |text: &str, pos: Vec2<f32>, align: f32, size: f32| {
+        renderer.draw_text_wrapped(
+            framebuffer,
+            &camera,
+            text,
+            pos,
+            align,
+            size,
+            column_width,
+            font,
+            Color::BLACK,
+        );
+    };
+
+    text(info.title, vec2(left, top - TITLE_SIZE), 0.0, TITLE_SIZE);
+    if let Some(hint) = info.hint {
+        text(
+            hint,
+            vec2(left, top - TITLE_SIZE - TEXT_SIZE * 2.0),
+            0.0,
+            TEXT_SIZE,
+        );
+    }
+
+    let moves = match info.par {
+        Some(par) => format!("moves {} / par {}", info.moves, par),
+        None => format!("moves {}", info.moves),
+    };
+    text(&moves, vec2(right, top - TEXT_SIZE), 1.0, TEXT_SIZE);
+    let objective = if info.targets.is_empty() {
+        "nothing left to eat".to_owned()
+    } else {
+        let targets: Vec<String> = info
+            .targets
+            .iter()
+            .map(|&(target, count)| format!("{} {}", count, target_name(target, count)))
+            .collect();
+        format!("eat {}", targets.join(", "))
+    };
+    text(
+        &objective,
+        vec2(right, top - TEXT_SIZE * 2.5),
+        1.0,
+        TEXT_SIZE,
+    );
+
+    if let Some(message) = info.message {
+        renderer.draw_text(
+            framebuffer,
+            &camera,
+            message,
+            vec2(0.0, 1.0),
+            0.5,
+            1.0,
+            font,
+            Color::BLACK,
+        );
+    }
+
+    if let Some(notice) = info.notice {
+        renderer.draw_text_wrapped(
+            framebuffer,
+            &camera,
+            notice,
+            vec2(0.0, -1.0),
+            0.5,
+            TEXT_SIZE * 1.25,
+            half_width * 2.0 - MARGIN * 2.0,
+            font,
+            Color::BLACK,
+        );
+    }
+
+    for (button, aabb) in hud_buttons(framebuffer_size) {
+        renderer.draw_text(
+            framebuffer,
+            &camera,
+            button.label(),
+            vec2(aabb.x_max, aabb.y_min),
+            1.0,
+            TEXT_SIZE,
+            font,
+            Color::rgb(0.3, 0.3, 0.3),
+        );
+    }
+
+    let controls_width = half_width * 2.0 - MARGIN * 2.0;
+    let controls = wrap_text(info.controls, (controls_width / TEXT_SIZE) as usize).join("\n");
+    let controls_height = measure_text(&controls, TEXT_SIZE).y;
+    renderer.draw_text(
+        framebuffer,
+        &camera,
+        &controls,
+        vec2(0.0, -half_height + MARGIN + controls_height - TEXT_SIZE),
+        0.5,
+        TEXT_SIZE,
+        font,
+        Color::BLACK,
+    );
+}
+
+fn target_name(target: EntityType, count: usize) -> String {
+    if count == 1 {
+        target.name().to_lowercase()
+    } else {
+        target.plural_name().to_lowercase()
+    }
+}
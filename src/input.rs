@@ -0,0 +1,275 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, IntoEnumIterator, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Wait,
+    Undo,
+    Restart,
+    Pause,
+    Camera,
+    ToggleIntents,
+    /// Held together with a move to show its outcome instead of making it.
+    Preview,
+    Hint,
+    Playtest,
+    ToggleMetadata,
+    ToggleSaveSolution,
+    Mirror,
+    Rotate,
+    Delete,
+    Deselect,
+    /// Sets the level bounds to the selection, or cycles the edge behaviour.
+    Bounds,
+    /// Switches between painting entities and terrain.
+    ToggleLayer,
+}
+
+impl Action {
+    pub fn in_game(self) -> bool {
+        use Action::*;
+        !matches!(
+            self,
+            Playtest
+                | ToggleMetadata
+                | ToggleSaveSolution
+                | Mirror
+                | Rotate
+                | Delete
+                | Deselect
+                | Bounds
+                | ToggleLayer
+        )
+    }
+    pub fn in_editor(self) -> bool {
+        use Action::*;
+        !matches!(
+            self,
+            Wait | Undo | Restart | Pause | Camera | Preview | Hint
+        )
+    }
+    /// Whether both actions can be triggered in the same place, so they must not share a key.
+    pub fn shares_context(self, other: Self) -> bool {
+        (self.in_game() && other.in_game()) || (self.in_editor() && other.in_editor())
+    }
+    pub fn direction(self) -> Option<Move> {
+        match self {
+            Action::MoveUp => Some(Move::Up),
+            Action::MoveDown => Some(Move::Down),
+            Action::MoveLeft => Some(Move::Left),
+            Action::MoveRight => Some(Move::Right),
+            _ => None,
+        }
+    }
+    /// The move this action makes on the level, if any.
+    pub fn player_move(self) -> Option<Move> {
+        match self {
+            Action::Wait => Some(Move::Wait),
+            _ => self.direction(),
+        }
+    }
+    pub fn from_move(player_move: Move) -> Self {
+        match player_move {
+            Move::Wait => Action::Wait,
+            Move::Up => Action::MoveUp,
+            Move::Down => Action::MoveDown,
+            Move::Left => Action::MoveLeft,
+            Move::Right => Action::MoveRight,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        use Action::*;
+        match self {
+            MoveUp => "move up",
+            MoveDown => "move down",
+            MoveLeft => "move left",
+            MoveRight => "move right",
+            Wait => "wait",
+            Undo => "undo",
+            Restart => "restart",
+            Pause => "pause",
+            Camera => "camera mode",
+            ToggleIntents => "show animal plans",
+            Preview => "preview modifier",
+            Hint => "hint",
+            Playtest => "editor playtest",
+            ToggleMetadata => "editor metadata",
+            ToggleSaveSolution => "editor save solution",
+            Mirror => "editor mirror",
+            Rotate => "editor rotate",
+            Delete => "editor delete",
+            Deselect => "editor deselect",
+            Bounds => "editor level bounds",
+            ToggleLayer => "editor terrain layer",
+        }
+    }
+}
+
+/// Extra movement keys, on top of the arrows which are always bound.
+#[derive(Clone, Copy, PartialEq, Eq, IntoEnumIterator, Debug)]
+pub enum MovementPreset {
+    Arrows,
+    Wasd,
+    Hjkl,
+}
+
+impl MovementPreset {
+    /// Keys for up, down, left and right.
+    pub fn keys(self) -> Option<[geng::Key; 4]> {
+        use geng::Key::*;
+        match self {
+            Self::Arrows => None,
+            Self::Wasd => Some([W, S, A, D]),
+            Self::Hjkl => Some([K, J, H, L]),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Arrows => "arrows",
+            Self::Wasd => "arrows/wasd",
+            Self::Hjkl => "arrows/hjkl",
+        }
+    }
+}
+
+pub const MOVE_ACTIONS: [Action; 4] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+];
+
+/// Always bound to the move actions, in the order of `MOVE_ACTIONS`.
+pub const ARROW_KEYS: [geng::Key; 4] = [
+    geng::Key::Up,
+    geng::Key::Down,
+    geng::Key::Left,
+    geng::Key::Right,
+];
+
+/// Serializable mirror of the gamepad buttons we support.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadButton {
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    South,
+    East,
+    North,
+    West,
+    Start,
+    Select,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GamepadButton {
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+        use gilrs::Button;
+        Some(match button {
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            Button::South => Self::South,
+            Button::East => Self::East,
+            Button::North => Self::North,
+            Button::West => Self::West,
+            Button::Start => Self::Start,
+            Button::Select => Self::Select,
+            Button::LeftTrigger => Self::LeftTrigger,
+            Button::RightTrigger => Self::RightTrigger,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GamepadButton {
+    /// Button at `index` in the standard mapping of the browser Gamepad API.
+    fn from_standard_index(index: usize) -> Option<Self> {
+        Some(match index {
+            0 => Self::South,
+            1 => Self::East,
+            2 => Self::West,
+            3 => Self::North,
+            4 => Self::LeftTrigger,
+            5 => Self::RightTrigger,
+            8 => Self::Select,
+            9 => Self::Start,
+            12 => Self::DPadUp,
+            13 => Self::DPadDown,
+            14 => Self::DPadLeft,
+            15 => Self::DPadRight,
+            _ => return None,
+        })
+    }
+}
+
+/// Connected gamepads, through gilrs on desktop and the Gamepad API in the browser.
+pub struct Gamepad {
+    #[cfg(not(target_arch = "wasm32"))]
+    gilrs: Option<gilrs::Gilrs>,
+    /// The browser only reports which buttons are down, so presses are found
+    /// by comparing with the buttons held on the last poll.
+    #[cfg(target_arch = "wasm32")]
+    held: HashSet<(u32, GamepadButton)>,
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            gilrs: gilrs::Gilrs::new().ok(),
+            #[cfg(target_arch = "wasm32")]
+            held: HashSet::new(),
+        }
+    }
+    /// Buttons pressed since the last call.
+    pub fn poll(&mut self) -> Vec<GamepadButton> {
+        #[allow(unused_mut)]
+        let mut pressed = Vec::new();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(gilrs) = &mut self.gilrs {
+                while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+                    if let gilrs::EventType::ButtonPressed(button, _) = event {
+                        pressed.extend(GamepadButton::from_gilrs(button));
+                    }
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let mut held = HashSet::new();
+            let gamepads =
+                web_sys::window().and_then(|window| window.navigator().get_gamepads().ok());
+            for gamepad in gamepads.iter().flat_map(|gamepads| gamepads.iter()) {
+                // Disconnected slots are null
+                let gamepad: web_sys::Gamepad = match gamepad.dyn_into() {
+                    Ok(gamepad) => gamepad,
+                    Err(_) => continue,
+                };
+                for (index, button) in gamepad.buttons().iter().enumerate() {
+                    let button: web_sys::GamepadButton = button.unchecked_into();
+                    if let Some(button) =
+                        GamepadButton::from_standard_index(index).filter(|_| button.pressed())
+                    {
+                        if !self.held.contains(&(gamepad.index(), button)) {
+                            pressed.push(button);
+                        }
+                        held.insert((gamepad.index(), button));
+                    }
+                }
+            }
+            self.held = held;
+        }
+        pressed
+    }
+}
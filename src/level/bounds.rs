@@ -0,0 +1,145 @@
+use super::*;
+
+/// What happens to entities stepping over the edge of the level.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, IntoEnumIterator, Debug)]
+pub enum EdgeBehaviour {
+    Solid,
+    /// Comes out on the opposite side.
+    Wrap,
+    /// Leaves the level and is removed.
+    Despawn,
+}
+
+impl EdgeBehaviour {
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeBehaviour::Solid => "solid",
+            EdgeBehaviour::Wrap => "wrap",
+            EdgeBehaviour::Despawn => "despawn",
+        }
+    }
+}
+
+/// Inclusive rectangle of tiles the level is played in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LevelBounds {
+    pub min: Vec2<i32>,
+    pub max: Vec2<i32>,
+    pub edge: EdgeBehaviour,
+}
+
+impl LevelBounds {
+    pub fn contains(&self, position: Vec2<i32>) -> bool {
+        self.min.x <= position.x
+            && position.x <= self.max.x
+            && self.min.y <= position.y
+            && position.y <= self.max.y
+    }
+    pub fn size(&self) -> Vec2<i32> {
+        self.max - self.min + vec2(1, 1)
+    }
+    /// Where a step from `position` ends up, or `None` if the edge stops it.
+    /// With despawning edges the result may lie outside of the bounds.
+    pub fn step(&self, position: Vec2<i32>, direction: Vec2<i32>) -> Option<Vec2<i32>> {
+        let next_pos = position + direction;
+        if self.contains(next_pos) {
+            return Some(next_pos);
+        }
+        match self.edge {
+            EdgeBehaviour::Solid => None,
+            EdgeBehaviour::Wrap => {
+                let size = self.size();
+                Some(vec2(
+                    self.min.x + (next_pos.x - self.min.x).rem_euclid(size.x),
+                    self.min.y + (next_pos.y - self.min.y).rem_euclid(size.y),
+                ))
+            }
+            EdgeBehaviour::Despawn => Some(next_pos),
+        }
+    }
+}
+
+impl Level {
+    pub fn in_bounds(&self, position: Vec2<i32>) -> bool {
+        self.bounds.map_or(true, |bounds| bounds.contains(position))
+    }
+    /// Applies the edge behaviour to a single step.
+    pub fn step_pos(&self, position: Vec2<i32>, direction: Vec2<i32>) -> Option<Vec2<i32>> {
+        match &self.bounds {
+            Some(bounds) => bounds.step(position, direction),
+            None => Some(position + direction),
+        }
+    }
+    /// Removes everything that walked or was pushed over a despawning edge.
+    pub(super) fn despawn_out_of_bounds(&mut self) {
+        let ids: Vec<Id> = self
+            .entities
+            .iter()
+            .filter(|(_, entity)| !self.in_bounds(entity.position))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in ids {
+            if let Some(entity) = self.entities.remove(&id) {
+                self.events.push(TurnEvent::LeftLevel { entity });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(edge: EdgeBehaviour) -> LevelBounds {
+        LevelBounds {
+            min: vec2(0, 0),
+            max: vec2(2, 1),
+            edge,
+        }
+    }
+
+    #[test]
+    fn steps_inside_are_never_changed() {
+        for edge in EdgeBehaviour::into_enum_iter() {
+            assert_eq!(bounds(edge).step(vec2(0, 0), vec2(1, 0)), Some(vec2(1, 0)));
+            assert_eq!(bounds(edge).step(vec2(2, 1), vec2(0, -1)), Some(vec2(2, 0)));
+        }
+    }
+
+    #[test]
+    fn solid_edges_stop_steps() {
+        let bounds = bounds(EdgeBehaviour::Solid);
+        assert_eq!(bounds.step(vec2(2, 0), vec2(1, 0)), None);
+        assert_eq!(bounds.step(vec2(0, 0), vec2(0, -1)), None);
+    }
+
+    #[test]
+    fn wrapping_edges_come_out_on_the_opposite_side() {
+        let bounds = bounds(EdgeBehaviour::Wrap);
+        assert_eq!(bounds.step(vec2(2, 0), vec2(1, 0)), Some(vec2(0, 0)));
+        assert_eq!(bounds.step(vec2(0, 1), vec2(-1, 0)), Some(vec2(2, 1)));
+        assert_eq!(bounds.step(vec2(1, 1), vec2(0, 1)), Some(vec2(1, 0)));
+        assert_eq!(bounds.step(vec2(1, 0), vec2(0, -1)), Some(vec2(1, 1)));
+    }
+
+    #[test]
+    fn despawning_edges_remove_what_leaves() {
+        let bounds = bounds(EdgeBehaviour::Despawn);
+        assert_eq!(bounds.step(vec2(2, 0), vec2(1, 0)), Some(vec2(3, 0)));
+        let mut level = Level::empty();
+        level.bounds = Some(bounds);
+        for &position in &[vec2(2, 0), vec2(3, 0)] {
+            level.set_entity(Entity {
+                position,
+                render_pos: position.map(|x| x as f32),
+                entity_type: EntityType::Box,
+                controller: None,
+                animation: default(),
+            });
+        }
+        level.despawn_out_of_bounds();
+        assert_eq!(level.entities.len(), 1);
+        assert!(level.get_entity(vec2(2, 0)).is_some());
+        assert!(matches!(level.events[..], [TurnEvent::LeftLevel { .. }]));
+    }
+}
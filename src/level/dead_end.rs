@@ -0,0 +1,76 @@
+use super::*;
+
+impl Level {
+    /// Whether the player can no longer win because a target is cut off
+    /// by walls, fences, water and other fixed obstacles.
+    /// Run a `Solver` as well to catch the rest of the unwinnable states.
+    pub fn is_dead_end(&self) -> bool {
+        self.get_state() == LevelState::Playing && !self.targets_reachable()
+    }
+    /// Flood fills from the player through everything that can ever get out of the way.
+    fn targets_reachable(&self) -> bool {
+        let player = match self.get_player() {
+            Some(player) => player,
+            None => return false,
+        };
+        let targets = player.entity_type.attractors();
+        // Animals can go places the player can't, like mice under fences, and come back out
+        if self
+            .entities
+            .values()
+            .any(|entity| targets.contains(&entity.entity_type) && entity.controller.is_some())
+        {
+            return true;
+        }
+        let target_positions: HashSet<Vec2<i32>> = self
+            .entities
+            .values()
+            .filter(|entity| targets.contains(&entity.entity_type))
+            .map(|entity| entity.position)
+            .collect();
+        // Water can still be filled in while there is something to push into it
+        let can_bridge = self.entities.values().any(|entity| {
+            entity.entity_type.property() == Some(EntityProperty::Pushable)
+                && !targets.contains(&entity.entity_type)
+        });
+        let (min, max) = match self.bounds {
+            Some(bounds) => (bounds.min, bounds.max),
+            None => {
+                // Past the outermost entity everything is open grass
+                let mut min = player.position;
+                let mut max = player.position;
+                for entity in self.entities.values() {
+                    min = vec2(min.x.min(entity.position.x), min.y.min(entity.position.y));
+                    max = vec2(max.x.max(entity.position.x), max.y.max(entity.position.y));
+                }
+                (min + vec2(-1, -1), max + vec2(1, 1))
+            }
+        };
+
+        let mut used = HashSet::new();
+        let mut queue = std::collections::VecDeque::new();
+        used.insert(player.position);
+        queue.push_back(player.position);
+        while let Some(pos) = queue.pop_front() {
+            for &direction in &[vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1)] {
+                let next = match self.step_pos(pos, direction) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                // Targets can be eaten from next to them, even where the player can't go
+                if target_positions.contains(&next) {
+                    return true;
+                }
+                if next.x < min.x || next.y < min.y || next.x > max.x || next.y > max.y {
+                    continue;
+                }
+                let passable = self.is_passable(player.entity_type, next)
+                    || (can_bridge && self.terrain_at(next) == Terrain::Water);
+                if passable && used.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        false
+    }
+}
@@ -11,12 +11,57 @@ pub struct Entity {
     pub render_pos: Vec2<f32>,
     pub entity_type: EntityType,
     pub controller: Option<EntityController>,
+    #[serde(default, skip)]
+    pub animation: EntityAnimation,
 }
 
 impl Entity {
     pub fn distance(&self, other: &Self) -> i32 {
         position_distance(self.position, other.position)
     }
+    /// Maps every position stored in the entity, including chain origin and memory.
+    pub fn transform(&mut self, f: impl Fn(Vec2<i32>) -> Vec2<i32>) {
+        self.position = f(self.position);
+        self.render_pos = self.position.map(|x| x as f32);
+        if let Some(controller) = &mut self.controller {
+            controller.last_attractor_pos = controller.last_attractor_pos.map(&f);
+            if let ControllerType::Dog { chain: Some(chain) } = &mut controller.controller_type {
+                chain.origin = f(chain.origin);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepKind {
+    Walk,
+    Push,
+    Pushed,
+}
+
+/// Render-only state describing the last step of an entity.
+#[derive(Clone, Debug)]
+pub struct EntityAnimation {
+    /// Sprites face right, so this is only changed by horizontal moves.
+    pub facing_left: bool,
+    pub step: Option<StepKind>,
+}
+
+impl Default for EntityAnimation {
+    fn default() -> Self {
+        Self {
+            facing_left: false,
+            step: None,
+        }
+    }
+}
+
+impl EntityAnimation {
+    pub fn turn_to(&mut self, direction: Vec2<i32>) {
+        if direction.x != 0 {
+            self.facing_left = direction.x < 0;
+        }
+    }
 }
 
 pub fn position_distance(pos1: Vec2<i32>, pos2: Vec2<i32>) -> i32 {
@@ -33,6 +78,15 @@ pub enum Move {
 }
 
 impl Move {
+    pub fn name(self) -> &'static str {
+        match self {
+            Move::Wait => "wait",
+            Move::Up => "up",
+            Move::Down => "down",
+            Move::Left => "left",
+            Move::Right => "right",
+        }
+    }
     pub fn direction(self) -> Vec2<i32> {
         match self {
             Move::Wait => vec2(0, 0),
@@ -66,11 +120,40 @@ pub enum EntityType {
     Bone,
     Fence,
     Wall,
-    Water,
     Fish,
 }
 
 impl EntityType {
+    pub fn name(self) -> &'static str {
+        match self {
+            EntityType::Bush => "Bush",
+            EntityType::Cat => "Cat",
+            EntityType::Dog => "Dog",
+            EntityType::Mouse => "Mouse",
+            EntityType::Doghouse => "Doghouse",
+            EntityType::Box => "Box",
+            EntityType::Cheese => "Cheese",
+            EntityType::Bone => "Bone",
+            EntityType::Fence => "Fence",
+            EntityType::Wall => "Wall",
+            EntityType::Fish => "Fish",
+        }
+    }
+    pub fn plural_name(self) -> &'static str {
+        match self {
+            EntityType::Bush => "Bushes",
+            EntityType::Cat => "Cats",
+            EntityType::Dog => "Dogs",
+            EntityType::Mouse => "Mice",
+            EntityType::Doghouse => "Doghouses",
+            EntityType::Box => "Boxes",
+            EntityType::Cheese => "Cheese",
+            EntityType::Bone => "Bones",
+            EntityType::Fence => "Fences",
+            EntityType::Wall => "Walls",
+            EntityType::Fish => "Fish",
+        }
+    }
     pub fn enemies(&self) -> Vec<Self> {
         use EntityType::*;
         match self {
@@ -89,10 +172,14 @@ impl EntityType {
             _ => vec![],
         }
     }
+    /// Mice are small enough to fit under fences.
+    pub fn passes_under(&self, other: Self) -> bool {
+        matches!((self, other), (EntityType::Mouse, EntityType::Fence))
+    }
     pub fn property(&self) -> Option<EntityProperty> {
         use EntityType::*;
         match self {
-            Bush | Doghouse | Fence | Wall | Water => Some(EntityProperty::Collidable),
+            Bush | Doghouse | Fence | Wall => Some(EntityProperty::Collidable),
             Box | Cheese | Bone => Some(EntityProperty::Pushable),
             _ => None,
         }
@@ -110,6 +197,8 @@ pub struct EntityController {
     pub next_move: Move,
     pub last_attractor_pos: Option<Vec2<i32>>,
     pub controller_type: ControllerType,
+    #[serde(default)]
+    pub view_radius: Option<i32>,
 }
 
 impl EntityController {
@@ -125,8 +214,17 @@ impl EntityController {
             next_move: Move::Wait,
             last_attractor_pos: None,
             controller_type,
+            view_radius: None,
         })
     }
+    pub fn player() -> Self {
+        Self {
+            next_move: Move::Wait,
+            last_attractor_pos: None,
+            controller_type: ControllerType::Player,
+            view_radius: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
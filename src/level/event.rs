@@ -0,0 +1,31 @@
+use super::*;
+
+/// Something that happened during a turn that the player should see.
+#[derive(Clone, Debug)]
+pub enum TurnEvent {
+    Eaten {
+        entity: Entity,
+        by: EntityType,
+    },
+    LeftLevel {
+        entity: Entity,
+    },
+    /// Pushed into water, leaving a bridge behind.
+    Sunk {
+        entity: Entity,
+    },
+}
+
+impl Level {
+    pub(super) fn eat(&mut self, id: Id, by: EntityType) {
+        if let Some(entity) = self.entities.remove(&id) {
+            self.events.push(TurnEvent::Eaten { entity, by });
+        }
+    }
+    pub(super) fn sink(&mut self, id: Id) {
+        if let Some(entity) = self.entities.remove(&id) {
+            self.set_terrain(entity.position, Terrain::Bridge);
+            self.events.push(TurnEvent::Sunk { entity });
+        }
+    }
+}
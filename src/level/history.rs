@@ -0,0 +1,160 @@
+use super::*;
+
+/// What a turn can change: the entities and the bridges built so far.
+pub(super) struct Snapshot {
+    pub entities: HashMap<Id, Entity>,
+    pub bridges: Vec<Vec2<i32>>,
+}
+
+impl Snapshot {
+    pub fn new(level: &Level) -> Self {
+        Self {
+            entities: level.entities.clone(),
+            bridges: level.bridges(),
+        }
+    }
+    /// Puts `level` back into this state.
+    pub fn restore(self, level: &mut Level) {
+        // Bridges are only ever built on water
+        for position in level.bridges() {
+            if !self.bridges.contains(&position) {
+                level.set_terrain(position, Terrain::Water);
+            }
+        }
+        for &position in &self.bridges {
+            level.set_terrain(position, Terrain::Bridge);
+        }
+        level.entities = self.entities;
+        level.events.clear();
+    }
+}
+
+/// States before each turn, for undo. Only the last `limit` turns are kept.
+pub struct History {
+    turns: std::collections::VecDeque<Snapshot>,
+    limit: usize,
+}
+
+impl History {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            turns: std::collections::VecDeque::new(),
+            limit,
+        }
+    }
+    /// Remembers `level` as it is before a turn.
+    pub fn push(&mut self, level: &Level) {
+        if self.turns.len() == self.limit {
+            self.turns.pop_front();
+        }
+        self.turns.push_back(Snapshot::new(level));
+    }
+    /// Puts `level` back to how it was before the last remembered turn.
+    /// Returns `false` if there is nothing left to undo.
+    pub fn undo(&mut self, level: &mut Level) -> bool {
+        match self.turns.pop_back() {
+            Some(snapshot) => snapshot.restore(level),
+            None => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(level: &Level) -> (Vec<(usize, Vec2<i32>)>, Vec<Vec2<i32>>) {
+        let mut entities: Vec<_> = level
+            .entities
+            .iter()
+            .map(|(id, entity)| (id.raw(), entity.position))
+            .collect();
+        entities.sort_by_key(|&(id, _)| id);
+        (entities, level.bridges())
+    }
+
+    #[test]
+    fn undo_goes_back_through_every_turn() {
+        let mut level: Level =
+            serde_json::from_str(include_str!("../../static/levels/level8.json")).unwrap();
+        let start = positions(&level);
+        let mut history = History::new(100);
+        let mut states = Vec::new();
+        let moves = [
+            Move::Right,
+            Move::Right,
+            Move::Right,
+            Move::Right,
+            Move::Up,
+            Move::Right,
+            Move::Down,
+            Move::Down,
+            Move::Up,
+            Move::Up,
+            Move::Up,
+            Move::Right,
+            Move::Right,
+            Move::Down,
+            Move::Down,
+            Move::Down,
+            Move::Down,
+            Move::Left,
+        ];
+        for &player_move in &moves {
+            states.push(positions(&level));
+            history.push(&level);
+            level.turn(player_move);
+        }
+        while let Some(state) = states.pop() {
+            assert!(history.undo(&mut level));
+            assert!(positions(&level) == state);
+        }
+        assert!(positions(&level) == start);
+        assert!(!history.undo(&mut level));
+    }
+
+    #[test]
+    fn undo_drains_bridges() {
+        let mut level = Level::empty();
+        for &(entity_type, position) in &[
+            (EntityType::Cat, vec2(0, 0)),
+            (EntityType::Box, vec2(1, 0)),
+            (EntityType::Mouse, vec2(0, 10)),
+        ] {
+            level.set_entity(Entity {
+                position,
+                render_pos: position.map(|x| x as f32),
+                entity_type,
+                controller: if entity_type == EntityType::Cat {
+                    Some(EntityController::player())
+                } else {
+                    EntityController::from_entity_type(entity_type)
+                },
+                animation: default(),
+            });
+        }
+        level.set_terrain(vec2(2, 0), Terrain::Water);
+        let mut history = History::new(100);
+        history.push(&level);
+        level.turn(Move::Right);
+        assert!(level.terrain_at(vec2(2, 0)) == Terrain::Bridge);
+        assert!(history.undo(&mut level));
+        assert!(level.terrain_at(vec2(2, 0)) == Terrain::Water);
+        assert!(level.get_entity(vec2(1, 0)).is_some());
+    }
+
+    #[test]
+    fn only_the_last_turns_are_kept() {
+        let mut level: Level =
+            serde_json::from_str(include_str!("../../static/levels/level1.json")).unwrap();
+        let mut history = History::new(2);
+        for _ in 0..3 {
+            history.push(&level);
+            level.turn(Move::Right);
+        }
+        assert!(history.undo(&mut level));
+        assert!(history.undo(&mut level));
+        assert!(!history.undo(&mut level));
+    }
+}
@@ -0,0 +1,36 @@
+use super::*;
+
+/// What an AI controlled entity is going to do on the next turn.
+pub struct Intent {
+    pub entity_type: EntityType,
+    pub position: Vec2<i32>,
+    pub next_move: Move,
+    pub view_radius: i32,
+    pub attractor_pos: Option<Vec2<i32>>,
+}
+
+impl Level {
+    /// Plans the next turn on a copy of the level.
+    /// Animals decide before the player moves, so the plan holds for any player move.
+    pub fn intents(&self) -> Vec<Intent> {
+        let mut level = self.clone();
+        level.calc_moves(Move::Wait);
+        level
+            .entities
+            .values()
+            .filter_map(|entity| {
+                let controller = entity.controller.as_ref()?;
+                if let ControllerType::Player = controller.controller_type {
+                    return None;
+                }
+                Some(Intent {
+                    entity_type: entity.entity_type,
+                    position: entity.position,
+                    next_move: controller.next_move,
+                    view_radius: controller.view_radius.unwrap_or(VIEW_RADIUS),
+                    attractor_pos: controller.last_attractor_pos,
+                })
+            })
+            .collect()
+    }
+}
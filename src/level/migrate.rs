@@ -0,0 +1,117 @@
+use super::*;
+
+/// Level as stored on disk, including what older versions of the format used.
+#[derive(Deserialize)]
+pub(super) struct LevelFile {
+    name: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    hint: Option<String>,
+    #[serde(default)]
+    par: Option<usize>,
+    #[serde(default)]
+    solution: Option<Vec<Move>>,
+    #[serde(default)]
+    bounds: Option<LevelBounds>,
+    id_generator: IdGenerator,
+    #[serde(default, deserialize_with = "terrain::deserialize_terrain")]
+    terrain: HashMap<Vec2<i32>, Terrain>,
+    entities: HashMap<Id, StoredEntity>,
+}
+
+#[derive(Deserialize)]
+struct StoredEntity {
+    position: Vec2<i32>,
+    entity_type: StoredEntityType,
+    controller: Option<EntityController>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredEntityType {
+    Current(EntityType),
+    Legacy(LegacyEntityType),
+}
+
+/// Entity types that have since been replaced.
+#[derive(Deserialize)]
+enum LegacyEntityType {
+    /// Now `Terrain::Water`.
+    Water,
+}
+
+impl From<LevelFile> for Level {
+    fn from(file: LevelFile) -> Self {
+        let mut level = Self {
+            name: file.name,
+            author: file.author,
+            hint: file.hint,
+            par: file.par,
+            solution: file.solution,
+            bounds: file.bounds,
+            id_generator: file.id_generator,
+            terrain: file.terrain,
+            entities: HashMap::new(),
+            events: Vec::new(),
+        };
+        for (id, entity) in file.entities {
+            match entity.entity_type {
+                StoredEntityType::Current(entity_type) => {
+                    level.entities.insert(
+                        id,
+                        Entity {
+                            position: entity.position,
+                            render_pos: entity.position.map(|x| x as f32),
+                            entity_type,
+                            controller: entity.controller,
+                            animation: default(),
+                        },
+                    );
+                }
+                StoredEntityType::Legacy(LegacyEntityType::Water) => {
+                    level.set_terrain(entity.position, Terrain::Water);
+                }
+            }
+        }
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_LEVEL: &str = include_str!("../../static/levels/level8.json");
+
+    #[test]
+    fn legacy_water_entities_become_terrain() {
+        let stored = LEGACY_LEVEL.matches("\"Water\"").count();
+        assert!(stored > 0, "the level should still be in the old format");
+        let level: Level = serde_json::from_str(LEGACY_LEVEL).unwrap();
+        let water: Vec<_> = level
+            .terrain
+            .iter()
+            .filter(|&(_, &terrain)| terrain == Terrain::Water)
+            .map(|(&position, _)| position)
+            .collect();
+        assert_eq!(water.len(), stored);
+        for position in water {
+            assert!(level.get_entity(position).is_none());
+        }
+    }
+
+    #[test]
+    fn current_format_survives_a_round_trip() {
+        let level: Level = serde_json::from_str(LEGACY_LEVEL).unwrap();
+        let saved = serde_json::to_string(&level).unwrap();
+        let loaded: Level = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.terrain, level.terrain);
+        assert_eq!(loaded.entities.len(), level.entities.len());
+        for (id, entity) in &level.entities {
+            let other = &loaded.entities[id];
+            assert_eq!(other.position, entity.position);
+            assert_eq!(other.entity_type, entity.entity_type);
+        }
+    }
+}
@@ -1,13 +1,29 @@
 use super::*;
 
+mod bounds;
+mod dead_end;
 mod entity;
+mod event;
+mod history;
 mod id;
+mod intent;
+mod migrate;
+mod passability;
 mod pathfind;
+mod solver;
+mod terrain;
 
+pub use bounds::*;
 pub use entity::*;
+pub use event::*;
+pub use history::*;
+pub use id::Id;
 use id::*;
+pub use intent::*;
+pub use solver::*;
+pub use terrain::*;
 
-const VIEW_RADIUS: i32 = 3;
+pub const VIEW_RADIUS: i32 = 3;
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum LevelState {
@@ -17,39 +33,64 @@ pub enum LevelState {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "migrate::LevelFile")]
 pub struct Level {
     pub name: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub hint: Option<String>,
+    #[serde(default)]
+    pub par: Option<usize>,
+    #[serde(default)]
+    pub solution: Option<Vec<Move>>,
+    #[serde(default)]
+    pub bounds: Option<LevelBounds>,
     id_generator: IdGenerator,
+    #[serde(
+        default,
+        serialize_with = "terrain::serialize_terrain",
+        deserialize_with = "terrain::deserialize_terrain"
+    )]
+    pub terrain: HashMap<Vec2<i32>, Terrain>,
     pub entities: HashMap<Id, Entity>,
+    /// Events of the last turn.
+    #[serde(skip)]
+    pub events: Vec<TurnEvent>,
 }
 
 impl Level {
     pub fn turn(&mut self, player_move: Move) {
+        self.events.clear();
         for entity in self.entities.values_mut() {
+            entity.animation.step = None;
             if let Some(c) = &mut entity.controller {
                 c.next_move = Move::Wait;
             }
         }
         self.calc_moves(player_move);
         self.make_moves();
+        self.despawn_out_of_bounds();
         self.collide();
     }
 
     pub fn empty() -> Self {
         Self {
             name: None,
+            author: None,
+            hint: None,
+            par: None,
+            solution: None,
+            bounds: None,
             id_generator: IdGenerator::new(),
+            terrain: HashMap::new(),
             entities: HashMap::new(),
+            events: Vec::new(),
         }
     }
 
     pub fn remove_entity(&mut self, position: Vec2<i32>) -> Option<Entity> {
-        if let Some(i) = self
-            .entities
-            .iter()
-            .find(|(_, entity)| entity.position == position)
-            .map(|(&i, _)| i)
-        {
+        if let Some(i) = self.get_entity(position).map(|(&i, _)| i) {
             self.entities.remove(&i)
         } else {
             None
@@ -62,10 +103,39 @@ impl Level {
         old_entity
     }
 
+    /// Animals may share a tile with what they pass under, in which case the animal is returned.
     pub fn get_entity(&self, position: Vec2<i32>) -> Option<(&Id, &Entity)> {
         self.entities
             .iter()
-            .find(|(_, entity)| entity.position == position)
+            .filter(|(_, entity)| entity.position == position)
+            .max_by_key(|(_, entity)| entity.controller.is_some())
+    }
+
+    /// Returns ids of all entities inside the inclusive rectangle.
+    pub fn entities_in(&self, min: Vec2<i32>, max: Vec2<i32>) -> Vec<Id> {
+        self.entities
+            .iter()
+            .filter(|(_, entity)| {
+                let pos = entity.position;
+                min.x <= pos.x && pos.x <= max.x && min.y <= pos.y && pos.y <= max.y
+            })
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    pub fn shift(&mut self, delta: Vec2<i32>) {
+        for entity in self.entities.values_mut() {
+            entity.transform(|pos| pos + delta);
+        }
+        self.terrain = self
+            .terrain
+            .drain()
+            .map(|(pos, terrain)| (pos + delta, terrain))
+            .collect();
+        if let Some(bounds) = &mut self.bounds {
+            bounds.min += delta;
+            bounds.max += delta;
+        }
     }
 
     fn get_entity_mut(&mut self, position: Vec2<i32>) -> Option<&mut Entity> {
@@ -84,9 +154,10 @@ impl Level {
                     let direction = match controller.controller_type {
                         ControllerType::Player => player_move.direction(),
                         _ => {
+                            let view_radius = controller.view_radius.unwrap_or(VIEW_RADIUS);
                             let enemies = entity.entity_type.enemies();
                             let attractors = entity.entity_type.attractors();
-                            self.get_move_direction(entity_id, VIEW_RADIUS, enemies, attractors)
+                            self.get_move_direction(entity_id, view_radius, enemies, attractors)
                         }
                     };
                     let next_move = Move::from_direction(direction).unwrap_or(Move::Wait);
@@ -138,11 +209,15 @@ impl Level {
     fn move_entity(
         &mut self,
         prev_pos: Option<Vec2<i32>>,
-        position: Vec2<i32>,
+        entity_id: Id,
         override_direction: Option<Vec2<i32>>,
         state: &mut HashSet<Id>,
     ) -> bool {
-        let (&entity_id, entity) = self.get_entity(position).unwrap();
+        let entity = match self.entities.get(&entity_id) {
+            Some(entity) => entity,
+            None => return false,
+        };
+        let position = entity.position;
         let entity_type = entity.entity_type;
         let direction = entity
             .controller
@@ -153,39 +228,79 @@ impl Level {
         if direction == vec2(0, 0) {
             return false;
         }
+        if entity.controller.is_none() && self.terrain_at(position) == Terrain::Mud {
+            return false;
+        }
         if !state.insert(entity_id) {
             return false;
         }
-        let next_pos = entity.position + direction;
-        let moved = if let Some((_, other)) = self.get_entity(next_pos) {
+        let next_pos = match self.step_pos(entity.position, direction) {
+            Some(next_pos) => next_pos,
+            None => return false,
+        };
+        // Despawning edges stop targets, since losing one would otherwise count towards a win
+        if !self.in_bounds(next_pos) && self.is_target(entity_type) {
+            return false;
+        }
+        // Pushables fill in the water they are pushed into, targets just block
+        let sinks = entity.controller.is_none()
+            && !self.is_target(entity_type)
+            && self.terrain_at(next_pos) == Terrain::Water
+            && self.in_bounds(next_pos)
+            && self.occupant(entity_type, next_pos).is_none();
+        let passable = sinks || self.is_passable(entity_type, next_pos);
+        let mut step = if entity.controller.is_some() {
+            StepKind::Walk
+        } else {
+            StepKind::Pushed
+        };
+        let moved = if let Some((&other_id, other)) = self.occupant(entity_type, next_pos) {
             let other_entity_type = other.entity_type;
             if !entity_type.attractors().contains(&other.entity_type)
                 && other.entity_type.property() == Some(EntityProperty::Pushable)
             {
-                self.move_entity(Some(position), next_pos, Some(direction), state)
-            } else if self.move_entity(Some(position), next_pos, None, state) {
+                if step == StepKind::Walk {
+                    step = StepKind::Push;
+                }
+                passable && self.move_entity(Some(position), other_id, Some(direction), state)
+            } else if passable && self.move_entity(Some(position), other_id, None, state) {
                 true
+            } else if !self.entities.contains_key(&entity_id) {
+                // Eaten from the neighbouring tile by what it was walking into
+                false
             } else if entity_type.attractors().contains(&other_entity_type) {
-                self.get_entity_mut(position)
-                    .unwrap()
-                    .controller
-                    .as_mut()
-                    .unwrap()
-                    .last_attractor_pos = None;
-                self.remove_entity(next_pos);
-                true
+                if let Some(controller) = &mut self.entities.get_mut(&entity_id).unwrap().controller
+                {
+                    controller.last_attractor_pos = None;
+                }
+                // Food out of reach, like fish in water, is eaten from the neighbouring tile
+                self.eat(other_id, entity_type);
+                passable
             } else if other_entity_type.attractors().contains(&entity_type) {
-                self.remove_entity(position);
+                // Walked into a predator, so it is eaten where the predator stands
+                self.entities.get_mut(&entity_id).unwrap().position = next_pos;
+                self.eat(entity_id, other_entity_type);
                 true
             } else {
                 false
             }
         } else {
-            true
+            passable
         };
         if moved {
-            if let Some(entity) = self.get_entity_mut(position) {
+            if let Some(entity) = self.entities.get_mut(&entity_id) {
+                if next_pos != position + direction {
+                    // Wrapped around the level, so there is nothing to animate in between
+                    entity.render_pos = next_pos.map(|x| x as f32);
+                }
                 entity.position = next_pos;
+                entity.animation.turn_to(direction);
+                entity.animation.step = Some(step);
+            }
+            if sinks {
+                self.sink(entity_id);
+            } else if self.terrain_at(next_pos) == Terrain::Ice {
+                self.slide(entity_id, direction);
             }
         }
         moved && Some(next_pos) != prev_pos
@@ -200,9 +315,7 @@ impl Level {
             .collect::<Vec<Id>>();
         let mut state = HashSet::new();
         for entity_id in entity_ids {
-            if let Some(entity) = self.entities.get(&entity_id) {
-                self.move_entity(None, entity.position, None, &mut state);
-            }
+            self.move_entity(None, entity_id, None, &mut state);
         }
     }
 
@@ -233,7 +346,34 @@ impl Level {
         }
     }
 
-    fn get_player(&self) -> Option<&Entity> {
+    /// Whether the player has to eat entities of this type.
+    pub fn is_target(&self, entity_type: EntityType) -> bool {
+        self.get_player().map_or(false, |player| {
+            player.entity_type.attractors().contains(&entity_type)
+        })
+    }
+
+    /// Counts the entities the player still has to eat, by type.
+    pub fn remaining_targets(&self) -> Vec<(EntityType, usize)> {
+        let targets = match self.get_player() {
+            Some(player) => player.entity_type.attractors(),
+            None => return Vec::new(),
+        };
+        targets
+            .into_iter()
+            .map(|target| {
+                let count = self
+                    .entities
+                    .values()
+                    .filter(|entity| entity.entity_type == target)
+                    .count();
+                (target, count)
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    pub fn get_player(&self) -> Option<&Entity> {
         self.entities.values().find(|entity| {
             if let Some(EntityController {
                 controller_type: ControllerType::Player,
@@ -289,7 +429,7 @@ impl Level {
             })
             .collect::<Vec<Id>>()
         {
-            self.entities.remove(&remove_id);
+            self.eat(remove_id, entity.entity_type);
         }
     }
 
@@ -363,14 +503,15 @@ impl Level {
         {
             let direction = -direction;
             let next_pos = entity.position + direction;
-            let direction = if self.is_empty(next_pos) {
+            let entity_type = entity.entity_type;
+            let direction = if self.is_empty(entity_type, next_pos) {
                 direction
             } else {
                 let direction = vec2(-direction.y, direction.x);
                 let delta = entity.position - avoid_pos;
                 if Vec2::dot(direction, delta) >= 0 {
                     let next_pos = entity.position + direction;
-                    if self.is_empty(next_pos) {
+                    if self.is_empty(entity_type, next_pos) {
                         direction
                     } else {
                         -direction
@@ -378,7 +519,7 @@ impl Level {
                 } else {
                     let direction = -direction;
                     let next_pos = entity.position + direction;
-                    if self.is_empty(next_pos) {
+                    if self.is_empty(entity_type, next_pos) {
                         direction
                     } else {
                         -direction
@@ -416,9 +557,12 @@ impl Level {
             return direction;
         } else if let Some(controller) = &entity.controller {
             if let Some(last_attractor_pos) = controller.last_attractor_pos {
-                if let Some(direction) =
-                    self.pathfind(entity.position, last_attractor_pos, VIEW_RADIUS * 2)
-                {
+                if let Some(direction) = self.pathfind(
+                    entity.entity_type,
+                    entity.position,
+                    last_attractor_pos,
+                    view_radius * 2,
+                ) {
                     if direction != vec2(0, 0) {
                         return direction;
                     }
@@ -429,8 +573,46 @@ impl Level {
         }
         vec2(0, 0)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn is_empty(&self, position: Vec2<i32>) -> bool {
-        self.get_entity(position).is_none()
+    const LEVELS: [&str; 9] = [
+        include_str!("../../static/levels/level1.json"),
+        include_str!("../../static/levels/level2.json"),
+        include_str!("../../static/levels/level3.json"),
+        include_str!("../../static/levels/level4.json"),
+        include_str!("../../static/levels/level5.json"),
+        include_str!("../../static/levels/level6.json"),
+        include_str!("../../static/levels/level7.json"),
+        include_str!("../../static/levels/level8.json"),
+        include_str!("../../static/levels/level9.json"),
+    ];
+
+    /// Catches rule changes that quietly break the shipped levels.
+    #[test]
+    fn shipped_levels_are_won_by_their_solutions() {
+        for (idx, json) in LEVELS.iter().enumerate() {
+            let mut level: Level = serde_json::from_str(json).unwrap();
+            let solution = level
+                .solution
+                .clone()
+                .expect("shipped levels store a solution");
+            for player_move in solution {
+                assert!(
+                    level.get_state() == LevelState::Playing,
+                    "level {} ended early",
+                    idx + 1
+                );
+                level.turn(player_move);
+            }
+            assert!(
+                level.get_state() == LevelState::Win,
+                "level {} is not won by its solution",
+                idx + 1
+            );
+        }
     }
 }
@@ -0,0 +1,38 @@
+use super::*;
+
+impl Level {
+    /// Whether `mover` may stand on the tile, ignoring animals and pushables
+    /// which can get out of the way.
+    pub fn is_passable(&self, mover: EntityType, position: Vec2<i32>) -> bool {
+        self.in_bounds(position)
+            && self.terrain_at(position).passable(mover)
+            && !self.entities.values().any(|entity| {
+                entity.position == position
+                    && entity.entity_type.property() == Some(EntityProperty::Collidable)
+                    && !mover.passes_under(entity.entity_type)
+            })
+    }
+    /// The entity `mover` runs into on the tile, skipping the ones it passes under.
+    pub(super) fn occupant(
+        &self,
+        mover: EntityType,
+        position: Vec2<i32>,
+    ) -> Option<(&Id, &Entity)> {
+        self.entities
+            .iter()
+            .filter(|(_, entity)| {
+                entity.position == position && !mover.passes_under(entity.entity_type)
+            })
+            .max_by_key(|(_, entity)| entity.controller.is_some())
+    }
+    /// Whether `mover` could step onto the tile right now.
+    pub(super) fn is_empty(&self, mover: EntityType, position: Vec2<i32>) -> bool {
+        self.is_passable(mover, position) && self.occupant(mover, position).is_none()
+    }
+    /// Whether animals can see through the tile.
+    pub(super) fn is_clear(&self, position: Vec2<i32>) -> bool {
+        self.in_bounds(position)
+            && !self.terrain_at(position).blocks_sight()
+            && self.get_entity(position).is_none()
+    }
+}
@@ -1,7 +1,14 @@
 use super::*;
 
 impl Level {
-    pub fn pathfind(&self, from: Vec2<i32>, to: Vec2<i32>, max_distance: i32) -> Option<Vec2<i32>> {
+    /// Direction of the first step of a path for `mover`.
+    pub fn pathfind(
+        &self,
+        mover: EntityType,
+        from: Vec2<i32>,
+        to: Vec2<i32>,
+        max_distance: i32,
+    ) -> Option<Vec2<i32>> {
         let mut queue = std::collections::VecDeque::new();
         let mut used = HashSet::new();
         queue.push_back(to);
@@ -9,7 +16,7 @@ impl Level {
         let mut max_iterations = max_distance;
         while let Some(pos) = queue.pop_front() {
             max_iterations -= 1;
-            if max_iterations == 0 {
+            if max_iterations <= 0 {
                 break;
             }
             for dx in -1..=1 {
@@ -17,16 +24,16 @@ impl Level {
                     if dx != 0 && dy != 0 {
                         continue;
                     }
-                    let next = pos + vec2(dx, dy);
+                    let next = match self.step_pos(pos, vec2(dx, dy)) {
+                        Some(next) if self.in_bounds(next) => next,
+                        _ => continue,
+                    };
                     if next == from {
-                        return Some(pos - from);
+                        return Some(vec2(-dx, -dy));
                     }
-                    if !used.contains(&next) {
-                        let entity = self.get_entity(next);
-                        if entity.is_none() {
-                            used.insert(next);
-                            queue.push_back(next);
-                        }
+                    if !used.contains(&next) && self.is_empty(mover, next) {
+                        used.insert(next);
+                        queue.push_back(next);
                     }
                 }
             }
@@ -54,7 +61,7 @@ impl Level {
             let check_pos = origin + delta * i as f32 / distance as f32;
             let mut empty = false;
             for check_tile in Self::get_tiles(check_pos, delta) {
-                if self.is_empty(check_tile) {
+                if self.is_clear(check_tile) {
                     empty = true;
                     if i == 1 {
                         direction = Some(check_tile - entity.position);
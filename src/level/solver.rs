@@ -0,0 +1,213 @@
+use super::*;
+
+const PLAYER_MOVES: [Move; 5] = [Move::Up, Move::Down, Move::Left, Move::Right, Move::Wait];
+
+pub enum SolveResult {
+    Solved(Vec<Move>),
+    /// Every reachable state was searched without finding a win.
+    Unsolvable,
+    /// The search gave up before finishing.
+    Unknown,
+}
+
+/// Everything that can change between turns: entities and the bridges made so far.
+type StateKey = (Vec<(Id, Vec2<i32>, Option<Vec2<i32>>)>, Vec<Vec2<i32>>);
+
+impl Level {
+    fn state_key(&self) -> StateKey {
+        let mut entities: Vec<_> = self
+            .entities
+            .iter()
+            .map(|(&id, entity)| {
+                let memory = entity
+                    .controller
+                    .as_ref()
+                    .and_then(|controller| controller.last_attractor_pos);
+                (id, entity.position, memory)
+            })
+            .collect();
+        entities.sort_by_key(|&(id, _, _)| id.raw());
+        let bridges = self.bridges();
+        (entities, bridges)
+    }
+}
+
+/// Breadth first search for the shortest solution, spread over as many calls as needed
+/// so that big searches don't hold up the game.
+pub struct Solver {
+    /// Starting level, which queued snapshots are restored onto.
+    level: Level,
+    visited: HashSet<StateKey>,
+    /// Parent and the move from it for every state reached, to rebuild the solution.
+    nodes: Vec<(Option<usize>, Move)>,
+    queue: std::collections::VecDeque<(Option<usize>, Snapshot)>,
+    max_states: usize,
+    result: Option<SolveResult>,
+}
+
+impl Solver {
+    /// Starts a search from the current state of `level`, visiting at most `max_states` states.
+    pub fn new(level: &Level, max_states: usize) -> Self {
+        let mut solver = Self {
+            level: level.clone(),
+            visited: HashSet::new(),
+            nodes: Vec::new(),
+            queue: std::collections::VecDeque::new(),
+            max_states,
+            result: None,
+        };
+        match level.get_state() {
+            LevelState::Win => solver.result = Some(SolveResult::Solved(Vec::new())),
+            LevelState::Loss => solver.result = Some(SolveResult::Unsolvable),
+            LevelState::Playing => {
+                let key = level.state_key();
+                let snapshot = Snapshot {
+                    entities: level.entities.clone(),
+                    bridges: key.1.clone(),
+                };
+                solver.visited.insert(key);
+                solver.queue.push_back((None, snapshot));
+            }
+        }
+        solver
+    }
+    /// Expands up to `budget` more states. Returns the result once the search is over.
+    pub fn step(&mut self, budget: usize) -> Option<&SolveResult> {
+        for _ in 0..budget {
+            if self.result.is_some() {
+                break;
+            }
+            self.result = self.expand();
+        }
+        self.result.as_ref()
+    }
+    fn restore(&self, snapshot: Snapshot) -> Level {
+        let mut level = self.level.clone();
+        snapshot.restore(&mut level);
+        level
+    }
+    fn moves(&self, mut node: Option<usize>) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while let Some(idx) = node {
+            let (parent, player_move) = self.nodes[idx];
+            moves.push(player_move);
+            node = parent;
+        }
+        moves.reverse();
+        moves
+    }
+    fn expand(&mut self) -> Option<SolveResult> {
+        let (node, snapshot) = match self.queue.pop_front() {
+            Some(state) => state,
+            None => return Some(SolveResult::Unsolvable),
+        };
+        let level = self.restore(snapshot);
+        for &player_move in &PLAYER_MOVES {
+            let mut next = level.clone();
+            next.turn(player_move);
+            let state = next.get_state();
+            if state == LevelState::Loss {
+                continue;
+            }
+            if state == LevelState::Win {
+                let mut moves = self.moves(node);
+                moves.push(player_move);
+                return Some(SolveResult::Solved(moves));
+            }
+            let key = next.state_key();
+            let bridges = key.1.clone();
+            if self.visited.insert(key) {
+                if self.visited.len() > self.max_states {
+                    return Some(SolveResult::Unknown);
+                }
+                self.nodes.push((node, player_move));
+                let snapshot = Snapshot {
+                    entities: next.entities,
+                    bridges,
+                };
+                self.queue.push_back((Some(self.nodes.len() - 1), snapshot));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(level: &Level, max_states: usize) -> SolveResult {
+        let mut solver = Solver::new(level, max_states);
+        loop {
+            if let Some(result) = solver.step(100) {
+                return match result {
+                    SolveResult::Solved(moves) => SolveResult::Solved(moves.clone()),
+                    SolveResult::Unsolvable => SolveResult::Unsolvable,
+                    SolveResult::Unknown => SolveResult::Unknown,
+                };
+            }
+        }
+    }
+
+    fn add(level: &mut Level, entity_type: EntityType, position: Vec2<i32>, player: bool) {
+        let controller = if player {
+            Some(EntityController::player())
+        } else {
+            EntityController::from_entity_type(entity_type)
+        };
+        level.set_entity(Entity {
+            position,
+            render_pos: position.map(|x| x as f32),
+            entity_type,
+            controller,
+            animation: default(),
+        });
+    }
+
+    /// Mouse player three tiles from cheese, shut in by walls if `walled`.
+    fn cheese_ahead(walled: bool) -> Level {
+        let mut level = Level::empty();
+        add(&mut level, EntityType::Mouse, vec2(0, 0), true);
+        add(&mut level, EntityType::Cheese, vec2(3, 0), false);
+        if walled {
+            for &position in &[vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1)] {
+                add(&mut level, EntityType::Wall, position, false);
+            }
+        }
+        level
+    }
+
+    #[test]
+    fn finds_the_shortest_solution() {
+        match solve(&cheese_ahead(false), 1000) {
+            SolveResult::Solved(moves) => assert_eq!(moves, vec![Move::Right; 3]),
+            _ => panic!("the cheese is in reach"),
+        }
+    }
+
+    #[test]
+    fn solves_a_shipped_level_in_as_few_moves_as_possible() {
+        let json = include_str!("../../static/levels/level3.json");
+        let level: Level = serde_json::from_str(json).unwrap();
+        match solve(&level, 100000) {
+            SolveResult::Solved(moves) => assert_eq!(moves.len(), 10),
+            _ => panic!("level 3 can be won"),
+        }
+    }
+
+    #[test]
+    fn runs_out_of_states_when_walled_off() {
+        assert!(matches!(
+            solve(&cheese_ahead(true), 1000),
+            SolveResult::Unsolvable
+        ));
+    }
+
+    #[test]
+    fn gives_up_past_the_state_limit() {
+        let mut level = Level::empty();
+        add(&mut level, EntityType::Mouse, vec2(0, 0), true);
+        add(&mut level, EntityType::Cheese, vec2(20, 20), false);
+        assert!(matches!(solve(&level, 100), SolveResult::Unknown));
+    }
+}
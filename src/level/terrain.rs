@@ -0,0 +1,175 @@
+use super::*;
+
+/// Ground under the entities. Tiles without terrain are grass.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, IntoEnumIterator, Debug)]
+pub enum Terrain {
+    Grass,
+    Water,
+    /// Water filled in with something pushed into it, walkable like grass.
+    Bridge,
+    /// Anything stepping or pushed onto ice slides until it hits something.
+    Ice,
+    /// Pushables get stuck in mud and can not be pushed out.
+    Mud,
+    Hole,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain::Grass
+    }
+}
+
+impl Terrain {
+    pub fn name(self) -> &'static str {
+        match self {
+            Terrain::Grass => "Grass",
+            Terrain::Water => "Water",
+            Terrain::Bridge => "Bridge",
+            Terrain::Ice => "Ice",
+            Terrain::Mud => "Mud",
+            Terrain::Hole => "Hole",
+        }
+    }
+    /// Fish only live in water, which dogs can swim through but cats and mice can't.
+    pub fn passable(self, entity_type: EntityType) -> bool {
+        use EntityType::*;
+        match self {
+            Terrain::Hole => false,
+            Terrain::Water => matches!(entity_type, Fish | Dog),
+            Terrain::Grass | Terrain::Bridge | Terrain::Ice | Terrain::Mud => entity_type != Fish,
+        }
+    }
+    pub fn blocks_sight(self) -> bool {
+        matches!(self, Terrain::Water | Terrain::Hole)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TerrainTile {
+    position: Vec2<i32>,
+    terrain: Terrain,
+}
+
+/// Terrain is stored as a list of tiles, since json only has string keys.
+pub(super) fn serialize_terrain<S: serde::Serializer>(
+    terrain: &HashMap<Vec2<i32>, Terrain>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut tiles: Vec<TerrainTile> = terrain
+        .iter()
+        .map(|(&position, &terrain)| TerrainTile { position, terrain })
+        .collect();
+    tiles.sort_by_key(|tile| (-tile.position.y, tile.position.x));
+    serde::Serialize::serialize(&tiles, serializer)
+}
+
+pub(super) fn deserialize_terrain<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<Vec2<i32>, Terrain>, D::Error> {
+    let tiles: Vec<TerrainTile> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(tiles
+        .into_iter()
+        .filter(|tile| tile.terrain != Terrain::Grass)
+        .map(|tile| (tile.position, tile.terrain))
+        .collect())
+}
+
+impl Level {
+    pub fn terrain_at(&self, position: Vec2<i32>) -> Terrain {
+        self.terrain.get(&position).copied().unwrap_or_default()
+    }
+    pub fn set_terrain(&mut self, position: Vec2<i32>, terrain: Terrain) {
+        if terrain == Terrain::Grass {
+            self.terrain.remove(&position);
+        } else {
+            self.terrain.insert(position, terrain);
+        }
+    }
+    /// Positions of all bridges, in a fixed order.
+    pub fn bridges(&self) -> Vec<Vec2<i32>> {
+        let mut bridges: Vec<Vec2<i32>> = self
+            .terrain
+            .iter()
+            .filter(|&(_, &terrain)| terrain == Terrain::Bridge)
+            .map(|(&pos, _)| pos)
+            .collect();
+        bridges.sort_by_key(|pos| (pos.x, pos.y));
+        bridges
+    }
+    /// Keeps an entity that just moved onto ice going in the same direction.
+    /// Stops in front of anything in the way, or after going all the way around a wrapping level.
+    /// Pushables sliding into water sink there, unless the player still has to eat them.
+    pub(super) fn slide(&mut self, entity_id: Id, direction: Vec2<i32>) {
+        let (entity_type, start, pushed) = match self.entities.get(&entity_id) {
+            Some(entity) => (
+                entity.entity_type,
+                entity.position,
+                entity.controller.is_none() && !self.is_target(entity.entity_type),
+            ),
+            None => return,
+        };
+        let mut position = start;
+        let mut wrapped = false;
+        let mut sinks = false;
+        while self.terrain_at(position) == Terrain::Ice && self.in_bounds(position) {
+            let next_pos = match self.step_pos(position, direction) {
+                Some(next_pos) => next_pos,
+                None => break,
+            };
+            sinks = pushed
+                && self.terrain_at(next_pos) == Terrain::Water
+                && self.in_bounds(next_pos)
+                && self.get_entity(next_pos).is_none();
+            if next_pos == start || !(sinks || self.is_empty(entity_type, next_pos)) {
+                break;
+            }
+            wrapped |= next_pos != position + direction;
+            position = next_pos;
+            if sinks {
+                break;
+            }
+        }
+        if let Some(entity) = self.entities.get_mut(&entity_id) {
+            entity.position = position;
+            if wrapped {
+                entity.render_pos = position.map(|x| x as f32);
+            }
+        }
+        if sinks {
+            self.sink(entity_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_dogs_and_fish_enter_water() {
+        for entity_type in EntityType::into_enum_iter() {
+            assert_eq!(
+                Terrain::Water.passable(entity_type),
+                matches!(entity_type, EntityType::Dog | EntityType::Fish)
+            );
+        }
+    }
+
+    #[test]
+    fn fish_never_leave_water() {
+        for terrain in Terrain::into_enum_iter() {
+            assert_eq!(
+                terrain.passable(EntityType::Fish),
+                terrain == Terrain::Water
+            );
+        }
+    }
+
+    #[test]
+    fn nothing_enters_holes() {
+        for entity_type in EntityType::into_enum_iter() {
+            assert!(!Terrain::Hole.passable(entity_type));
+        }
+    }
+}
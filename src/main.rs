@@ -2,46 +2,40 @@ use enum_iterator::IntoEnumIterator;
 use geng::prelude::*;
 
 mod assets;
+mod bindings;
 mod camera;
 mod editor;
+mod effects;
 mod game_state;
+mod hud;
+mod input;
 mod level;
+mod menu;
 mod model_renderer;
+mod progress;
 mod renderer;
+mod settings;
+mod storage;
 
 use assets::*;
+use bindings::*;
 use camera::*;
 use editor::*;
+use effects::*;
 use game_state::*;
+use hud::*;
+use input::*;
 use level::*;
+use menu::*;
 use model_renderer::*;
+use progress::*;
 use renderer::*;
+use settings::*;
 
 fn tile_pos(pos: Vec2<f32>) -> Vec2<i32> {
     pos.map(|x| x.floor() as i32)
 }
 
-impl Camera {
-    pub fn optimize(&mut self, level: &Level) {
-        let mut min_x = i32::MAX;
-        let mut min_y = i32::MAX;
-        let mut max_x = i32::MIN;
-        let mut max_y = i32::MIN;
-        for entity in level.entities.values() {
-            min_x = min_x.min(entity.position.x);
-            min_y = min_y.min(entity.position.y);
-            max_x = max_x.max(entity.position.x);
-            max_y = max_y.max(entity.position.y);
-        }
-        if min_x <= max_x {
-            max_x += 1;
-            max_y += 1;
-            self.fov = (max_y - min_y + 5) as f32;
-            self.center = vec2(min_x + max_x, min_y + max_y).map(|x| x as f32) / 2.0;
-        }
-    }
-}
-
 fn main() {
     geng::setup_panic_handler();
     if let Some(dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
@@ -66,14 +60,21 @@ fn main() {
             move |assets| {
                 let assets = assets.unwrap();
                 let assets = Rc::new(assets);
+                let settings = Rc::new(RefCell::new(Settings::load(&assets.bindings)));
+                settings.borrow().apply(&geng);
+                let gamepad = Rc::new(RefCell::new(Gamepad::new()));
+                let progress = Rc::new(RefCell::new(Progress::load()));
                 if std::env::args().any(|arg| arg == "editor") {
-                    Box::new(Editor::new(&geng, &assets)) as Box<dyn geng::State>
+                    Box::new(Editor::new(&geng, &assets, &settings, &gamepad, &progress))
+                        as Box<dyn geng::State>
                 } else {
-                    Box::new(GameState::new(
+                    Box::new(LevelSelect::new(
                         &geng,
                         &assets,
-                        assets.levels[0].clone(),
-                        Some(1),
+                        &settings,
+                        &gamepad,
+                        &progress,
+                        Some(0),
                     )) as Box<dyn geng::State>
                 }
             }
@@ -0,0 +1,132 @@
+use super::*;
+
+/// Root state of the game: lists the level pack and the settings.
+pub struct LevelSelect {
+    geng: Rc<Geng>,
+    assets: Rc<Assets>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    progress: Rc<RefCell<Progress>>,
+    renderer: Renderer,
+    menu: Menu,
+    /// Level to open right away, without showing the menu first.
+    autostart: Option<usize>,
+    framebuffer_size: Vec2<f32>,
+    transition: Option<geng::Transition>,
+}
+
+impl LevelSelect {
+    pub fn new(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+        autostart: Option<usize>,
+    ) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            progress: progress.clone(),
+            renderer: Renderer::new(geng),
+            menu: Menu::new(),
+            autostart,
+            framebuffer_size: vec2(1.0, 1.0),
+            transition: None,
+        }
+    }
+    fn items(&self) -> Vec<String> {
+        let progress = self.progress.borrow();
+        let mut items: Vec<String> = self
+            .assets
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(idx, level)| {
+                let stars = progress.levels.get(&idx).map_or(0, |best| best.stars);
+                format!(
+                    "{}. {} {}",
+                    idx + 1,
+                    level.name.as_deref().unwrap_or("unnamed level"),
+                    stars_text(stars)
+                )
+            })
+            .collect();
+        items.push("settings".to_owned());
+        #[cfg(not(target_arch = "wasm32"))]
+        items.push("quit".to_owned());
+        items
+    }
+    fn play(&mut self, level: usize) {
+        self.transition = Some(geng::Transition::Push(Box::new(GameState::new(
+            &self.geng,
+            &self.assets,
+            &self.settings,
+            &self.gamepad,
+            &self.progress,
+            self.assets.levels[level].clone(),
+            Some(level),
+        ))));
+    }
+    fn handle_menu_action(&mut self, action: Option<MenuAction>) {
+        let levels = self.assets.levels.len();
+        match action {
+            Some(MenuAction::Select(idx)) if idx < levels => self.play(idx),
+            Some(MenuAction::Select(idx)) if idx == levels => {
+                self.transition = Some(geng::Transition::Push(Box::new(SettingsScreen::new(
+                    &self.geng,
+                    &self.assets,
+                    &self.settings,
+                    &self.gamepad,
+                ))));
+            }
+            // Popping the first state ends the game loop
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(MenuAction::Select(_)) => self.transition = Some(geng::Transition::Pop),
+            // Quitting the game should not be one stray key or button away
+            _ => {}
+        }
+    }
+}
+
+impl geng::State for LevelSelect {
+    fn update(&mut self, _delta_time: f64) {
+        if let Some(level) = self.autostart.take() {
+            self.play(level);
+        }
+        let buttons = self.gamepad.borrow_mut().poll();
+        for button in buttons {
+            let action = self.settings.borrow().bindings.button_action(button);
+            if let Some(action) = action {
+                let action = self.menu.handle_action(action, self.items().len());
+                self.handle_menu_action(action);
+            }
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::clear(framebuffer, Some(MENU_BACKGROUND), None);
+        self.menu.draw(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            "cats, mice, and 8 pixels",
+            &self.items(),
+        );
+    }
+    fn handle_event(&mut self, event: geng::Event) {
+        let count = self.items().len();
+        let action = self.menu.handle_event(
+            &event,
+            &self.settings.borrow().bindings,
+            self.framebuffer_size,
+            count,
+        );
+        self.handle_menu_action(action);
+    }
+    fn transition(&mut self) -> Option<geng::Transition> {
+        self.transition.take()
+    }
+}
@@ -0,0 +1,247 @@
+use super::*;
+
+mod level_select;
+mod results;
+mod settings_screen;
+
+pub use level_select::*;
+pub use results::*;
+pub use settings_screen::*;
+
+pub const MENU_BACKGROUND: Color<f32> = Color {
+    r: 0.8,
+    g: 0.9,
+    b: 0.7,
+    a: 1.0,
+};
+const MENU_FOV: f32 = 10.0;
+const ITEM_SIZE: f32 = 0.5;
+const ITEM_SPACING: f32 = 0.8;
+const MAX_VISIBLE: usize = 9;
+
+#[derive(Clone, Copy)]
+pub enum MenuAction {
+    Select(usize),
+    /// Left or right on the item, for adjustable values.
+    Adjust(usize, i32),
+    Back,
+}
+
+/// Vertical list of items drawn with the bitmap font.
+/// Items are passed in on every call, so their labels can change freely.
+/// Long lists scroll to keep the selected item visible.
+pub struct Menu {
+    pub selected: usize,
+    scroll: usize,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            scroll: 0,
+        }
+    }
+    fn item_y(idx: usize, count: usize) -> f32 {
+        (count as f32 - 1.0) * ITEM_SPACING / 2.0 - idx as f32 * ITEM_SPACING - 0.5
+    }
+    fn visible(&self, count: usize) -> std::ops::Range<usize> {
+        self.scroll..(self.scroll + MAX_VISIBLE).min(count)
+    }
+    fn scroll_to_selected(&mut self, count: usize) {
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + MAX_VISIBLE {
+            self.scroll = self.selected + 1 - MAX_VISIBLE;
+        }
+        self.scroll = self.scroll.min(count.saturating_sub(MAX_VISIBLE));
+    }
+    fn hovered(
+        &self,
+        position: Vec2<f64>,
+        framebuffer_size: Vec2<f32>,
+        count: usize,
+    ) -> Option<usize> {
+        let position =
+            Camera::new(MENU_FOV).screen_to_world(framebuffer_size, position.map(|x| x as f32));
+        let visible_count = count.min(MAX_VISIBLE);
+        self.visible(count).find(|&idx| {
+            let y = Self::item_y(idx - self.scroll, visible_count);
+            y <= position.y && position.y < y + ITEM_SPACING
+        })
+    }
+    /// Actions that mean something in a menu.
+    fn is_menu_action(action: Action) -> bool {
+        matches!(
+            action,
+            Action::MoveUp
+                | Action::MoveDown
+                | Action::MoveLeft
+                | Action::MoveRight
+                | Action::Wait
+                | Action::Undo
+                | Action::Pause
+        )
+    }
+    /// Menu navigation with bound keys or gamepad buttons.
+    pub fn handle_action(&mut self, action: Action, count: usize) -> Option<MenuAction> {
+        if count == 0 {
+            return None;
+        }
+        self.selected = self.selected.min(count - 1);
+        let result = match action {
+            Action::MoveUp => {
+                self.selected = (self.selected + count - 1) % count;
+                None
+            }
+            Action::MoveDown => {
+                self.selected = (self.selected + 1) % count;
+                None
+            }
+            Action::MoveLeft => Some(MenuAction::Adjust(self.selected, -1)),
+            Action::MoveRight => Some(MenuAction::Adjust(self.selected, 1)),
+            Action::Wait => Some(MenuAction::Select(self.selected)),
+            Action::Undo | Action::Pause => Some(MenuAction::Back),
+            _ => None,
+        };
+        self.scroll_to_selected(count);
+        result
+    }
+    pub fn handle_event(
+        &mut self,
+        event: &geng::Event,
+        bindings: &KeyBindings,
+        framebuffer_size: Vec2<f32>,
+        count: usize,
+    ) -> Option<MenuAction> {
+        if count == 0 {
+            return None;
+        }
+        self.selected = self.selected.min(count - 1);
+        match *event {
+            geng::Event::KeyDown { key } => {
+                let action = match bindings.key_action(key, Self::is_menu_action) {
+                    Some(action) => action,
+                    None => return None,
+                };
+                return self.handle_action(action, count);
+            }
+            geng::Event::MouseMove { position, .. } => {
+                if let Some(idx) = self.hovered(position, framebuffer_size, count) {
+                    self.selected = idx;
+                }
+            }
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Left,
+            } => {
+                if let Some(idx) = self.hovered(position, framebuffer_size, count) {
+                    self.selected = idx;
+                    return Some(MenuAction::Select(idx));
+                }
+            }
+            geng::Event::Wheel { delta } => {
+                let max_scroll = count.saturating_sub(MAX_VISIBLE);
+                self.scroll = if delta > 0.0 {
+                    self.scroll.saturating_sub(1)
+                } else {
+                    (self.scroll + 1).min(max_scroll)
+                };
+                self.selected = clamp(
+                    self.selected,
+                    self.scroll..=self.scroll + MAX_VISIBLE.min(count) - 1,
+                );
+            }
+            _ => {}
+        }
+        None
+    }
+    pub fn draw(
+        &self,
+        renderer: &Renderer,
+        font: &ugli::Texture,
+        framebuffer: &mut ugli::Framebuffer,
+        title: &str,
+        items: &[String],
+    ) {
+        let camera = Camera::new(MENU_FOV);
+        let visible_count = items.len().min(MAX_VISIBLE);
+        renderer.draw_text(
+            framebuffer,
+            &camera,
+            title,
+            vec2(0.0, Self::item_y(0, visible_count) + ITEM_SPACING * 1.5),
+            0.5,
+            ITEM_SIZE * 1.5,
+            font,
+            Color::BLACK,
+        );
+        for idx in self.visible(items.len()) {
+            let text = if idx == self.selected {
+                format!("> {} <", items[idx])
+            } else {
+                items[idx].clone()
+            };
+            renderer.draw_text(
+                framebuffer,
+                &camera,
+                &text,
+                vec2(0.0, Self::item_y(idx - self.scroll, visible_count)),
+                0.5,
+                ITEM_SIZE,
+                font,
+                if idx == self.selected {
+                    Color::rgb(0.6, 0.2, 0.0)
+                } else {
+                    Color::BLACK
+                },
+            );
+        }
+        if self.scroll + visible_count < items.len() {
+            renderer.draw_text(
+                framebuffer,
+                &camera,
+                "...",
+                vec2(0.0, Self::item_y(visible_count, visible_count)),
+                0.5,
+                ITEM_SIZE,
+                font,
+                Color::rgb(0.3, 0.3, 0.3),
+            );
+        }
+    }
+    /// Extra lines of text under the items.
+    pub fn draw_footer(
+        &self,
+        renderer: &Renderer,
+        font: &ugli::Texture,
+        framebuffer: &mut ugli::Framebuffer,
+        count: usize,
+        lines: &[String],
+    ) {
+        let visible_count = count.min(MAX_VISIBLE);
+        renderer.draw_text(
+            framebuffer,
+            &Camera::new(MENU_FOV),
+            &lines.join("\n"),
+            vec2(
+                0.0,
+                Self::item_y(visible_count, visible_count) - ITEM_SPACING * 0.5,
+            ),
+            0.5,
+            ITEM_SIZE * 0.8,
+            font,
+            Color::BLACK,
+        );
+    }
+}
+
+/// Fades whatever was drawn before, so a menu can be drawn on top.
+pub fn draw_overlay(geng: &Geng, framebuffer: &mut ugli::Framebuffer) {
+    let size = framebuffer.size().map(|x| x as f32);
+    geng.draw_2d().quad(
+        framebuffer,
+        AABB::pos_size(vec2(0.0, 0.0), size),
+        Color::rgba(1.0, 1.0, 1.0, 0.7),
+    );
+}
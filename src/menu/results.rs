@@ -0,0 +1,293 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ResultsItem {
+    Next,
+    Finish,
+    Replay,
+    Retry,
+    LevelSelect,
+}
+
+/// Shown after winning a level of the pack.
+pub struct LevelResults {
+    geng: Rc<Geng>,
+    assets: Rc<Assets>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    progress: Rc<RefCell<Progress>>,
+    result: LevelResult,
+    new_best: bool,
+    renderer: Renderer,
+    menu: Menu,
+    framebuffer_size: Vec2<f32>,
+    transition: Option<geng::Transition>,
+}
+
+impl LevelResults {
+    pub fn new(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+        result: LevelResult,
+        new_best: bool,
+    ) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            progress: progress.clone(),
+            result,
+            new_best,
+            renderer: Renderer::new(geng),
+            menu: Menu::new(),
+            framebuffer_size: vec2(1.0, 1.0),
+            transition: None,
+        }
+    }
+    fn items(&self) -> Vec<(ResultsItem, &'static str)> {
+        let mut items = Vec::new();
+        if self.result.level_index + 1 < self.assets.levels.len() {
+            items.push((ResultsItem::Next, "next level"));
+        } else {
+            items.push((ResultsItem::Finish, "finish"));
+        }
+        items.push((ResultsItem::Replay, "watch replay"));
+        items.push((ResultsItem::Retry, "retry"));
+        items.push((ResultsItem::LevelSelect, "level select"));
+        items
+    }
+    fn level(&self, idx: usize) -> GameState {
+        GameState::new(
+            &self.geng,
+            &self.assets,
+            &self.settings,
+            &self.gamepad,
+            &self.progress,
+            self.assets.levels[idx].clone(),
+            Some(idx),
+        )
+    }
+    fn handle_menu_action(&mut self, action: Option<MenuAction>) {
+        let item = match action {
+            Some(MenuAction::Select(idx)) => self.items()[idx].0,
+            Some(MenuAction::Back) => ResultsItem::LevelSelect,
+            _ => return,
+        };
+        let level_index = self.result.level_index;
+        let state: Box<dyn geng::State> = match item {
+            ResultsItem::Next => Box::new(self.level(level_index + 1)),
+            ResultsItem::Finish => Box::new(PackSummary::new(
+                &self.geng,
+                &self.assets,
+                &self.settings,
+                &self.gamepad,
+                &self.progress,
+            )),
+            ResultsItem::Replay => Box::new(GameState::replay(
+                &self.geng,
+                &self.assets,
+                &self.settings,
+                &self.gamepad,
+                &self.progress,
+                self.assets.levels[level_index].clone(),
+                self.result.clone(),
+            )),
+            ResultsItem::Retry => Box::new(self.level(level_index)),
+            ResultsItem::LevelSelect => {
+                self.transition = Some(geng::Transition::Pop);
+                return;
+            }
+        };
+        self.transition = Some(geng::Transition::Switch(state));
+    }
+}
+
+impl geng::State for LevelResults {
+    fn update(&mut self, _delta_time: f64) {
+        let buttons = self.gamepad.borrow_mut().poll();
+        for button in buttons {
+            let action = self.settings.borrow().bindings.button_action(button);
+            if let Some(action) = action {
+                let action = self.menu.handle_action(action, self.items().len());
+                self.handle_menu_action(action);
+            }
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::clear(framebuffer, Some(MENU_BACKGROUND), None);
+        let result = &self.result;
+        let title = self.assets.levels[result.level_index]
+            .name
+            .clone()
+            .unwrap_or_else(|| "level complete".to_owned());
+        let items: Vec<String> = self
+            .items()
+            .into_iter()
+            .map(|(_, label)| label.to_owned())
+            .collect();
+        self.menu.draw(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            &title,
+            &items,
+        );
+        let mut info = vec![
+            stars_text(result.stars),
+            match result.par {
+                Some(par) => format!("{} moves, par {}", result.moves.len(), par),
+                None => format!("{} moves", result.moves.len()),
+            },
+            format!("time {}", format_time(result.time)),
+        ];
+        if result.hints > 0 {
+            info.push(format!("hints used {}", result.hints));
+        }
+        if self.new_best {
+            info.push("new best!".to_owned());
+        } else if let Some(best) = self
+            .progress
+            .borrow()
+            .levels
+            .get(&result.level_index)
+            .cloned()
+        {
+            info.push(format!(
+                "best {} moves, {}",
+                best.moves,
+                format_time(best.time)
+            ));
+        }
+        self.menu.draw_footer(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            items.len(),
+            &info,
+        );
+    }
+    fn handle_event(&mut self, event: geng::Event) {
+        let count = self.items().len();
+        let action = self.menu.handle_event(
+            &event,
+            &self.settings.borrow().bindings,
+            self.framebuffer_size,
+            count,
+        );
+        self.handle_menu_action(action);
+    }
+    fn transition(&mut self) -> Option<geng::Transition> {
+        self.transition.take()
+    }
+}
+
+/// Totals over the whole pack, shown after the last level.
+pub struct PackSummary {
+    assets: Rc<Assets>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    progress: Rc<RefCell<Progress>>,
+    renderer: Renderer,
+    menu: Menu,
+    framebuffer_size: Vec2<f32>,
+    transition: Option<geng::Transition>,
+}
+
+impl PackSummary {
+    pub fn new(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+        progress: &Rc<RefCell<Progress>>,
+    ) -> Self {
+        Self {
+            assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            progress: progress.clone(),
+            renderer: Renderer::new(geng),
+            menu: Menu::new(),
+            framebuffer_size: vec2(1.0, 1.0),
+            transition: None,
+        }
+    }
+    fn totals(&self) -> Vec<String> {
+        let progress = self.progress.borrow();
+        let levels = &self.assets.levels;
+        let completed: Vec<(&Level, &LevelProgress)> = levels
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, level)| Some((level, progress.levels.get(&idx)?)))
+            .collect();
+        let moves: usize = completed.iter().map(|(_, best)| best.moves).sum();
+        let par: usize = completed.iter().filter_map(|(level, _)| level.par).sum();
+        let stars: usize = completed.iter().map(|(_, best)| best.stars).sum();
+        let time: f64 = completed.iter().map(|(_, best)| best.time).sum();
+        let hints: usize = progress.hints.values().sum();
+        vec![
+            format!("{} of {} levels complete", completed.len(), levels.len()),
+            format!("stars {} / {}", stars, levels.len() * MAX_STARS),
+            format!("total moves {}, total par {}", moves, par),
+            format!("total time {}", format_time(time)),
+            format!("hints used {}", hints),
+        ]
+    }
+    fn handle_menu_action(&mut self, action: Option<MenuAction>) {
+        match action {
+            Some(MenuAction::Select(_)) | Some(MenuAction::Back) => {
+                self.transition = Some(geng::Transition::Pop);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl geng::State for PackSummary {
+    fn update(&mut self, _delta_time: f64) {
+        let buttons = self.gamepad.borrow_mut().poll();
+        for button in buttons {
+            let action = self.settings.borrow().bindings.button_action(button);
+            if let Some(action) = action {
+                let action = self.menu.handle_action(action, 1);
+                self.handle_menu_action(action);
+            }
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::clear(framebuffer, Some(MENU_BACKGROUND), None);
+        let items = vec!["back to menu".to_owned()];
+        self.menu.draw(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            "thanks for playing!",
+            &items,
+        );
+        self.menu.draw_footer(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            items.len(),
+            &self.totals(),
+        );
+    }
+    fn handle_event(&mut self, event: geng::Event) {
+        let action = self.menu.handle_event(
+            &event,
+            &self.settings.borrow().bindings,
+            self.framebuffer_size,
+            1,
+        );
+        self.handle_menu_action(action);
+    }
+    fn transition(&mut self) -> Option<geng::Transition> {
+        self.transition.take()
+    }
+}
@@ -0,0 +1,298 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Main,
+    Controls,
+    Palette,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Rebind {
+    Action(Action),
+    Palette(usize),
+}
+
+pub struct SettingsScreen {
+    geng: Rc<Geng>,
+    assets: Rc<Assets>,
+    settings: Rc<RefCell<Settings>>,
+    gamepad: Rc<RefCell<Gamepad>>,
+    renderer: Renderer,
+    menu: Menu,
+    page: Page,
+    rebinding: Option<Rebind>,
+    /// Key conflicts resolved by the last change.
+    notice: Option<String>,
+    framebuffer_size: Vec2<f32>,
+    transition: Option<geng::Transition>,
+}
+
+impl SettingsScreen {
+    pub fn new(
+        geng: &Rc<Geng>,
+        assets: &Rc<Assets>,
+        settings: &Rc<RefCell<Settings>>,
+        gamepad: &Rc<RefCell<Gamepad>>,
+    ) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            settings: settings.clone(),
+            gamepad: gamepad.clone(),
+            renderer: Renderer::new(geng),
+            menu: Menu::new(),
+            page: Page::Main,
+            rebinding: None,
+            notice: None,
+            framebuffer_size: vec2(1.0, 1.0),
+            transition: None,
+        }
+    }
+    fn items(&self) -> Vec<String> {
+        let settings = self.settings.borrow();
+        match self.page {
+            Page::Main => vec![
+                format!("volume < {:.0}% >", settings.master_volume * 100.0),
+                format!("sound effects < {:.0}% >", settings.sfx_volume * 100.0),
+                format!("animation speed < {:.2}x >", settings.animation_speed),
+                format!("key repeat delay < {:.2}s >", settings.repeat_delay),
+                format!("key repeat rate < {:.2}s >", settings.repeat_interval),
+                format!(
+                    "fullscreen: {}",
+                    if settings.fullscreen { "on" } else { "off" }
+                ),
+                format!(
+                    "dead end solver: {}",
+                    if settings.solver_dead_ends {
+                        "on"
+                    } else {
+                        "off"
+                    }
+                ),
+                "controls".to_owned(),
+                "editor palette".to_owned(),
+                "back".to_owned(),
+            ],
+            Page::Controls => {
+                let preset = match settings.bindings.movement_preset() {
+                    Some(preset) => preset.name(),
+                    None => "custom",
+                };
+                let mut items = vec![format!("movement < {} >", preset)];
+                items.extend(Action::into_enum_iter().map(|action| {
+                    let keys = if self.rebinding == Some(Rebind::Action(action)) {
+                        "press a key".to_owned()
+                    } else {
+                        settings.bindings.action_name(action)
+                    };
+                    format!("{}: {}", action.name(), keys)
+                }));
+                items.push("reset to defaults".to_owned());
+                items.push("back".to_owned());
+                items
+            }
+            Page::Palette => settings
+                .bindings
+                .palette
+                .iter()
+                .enumerate()
+                .map(|(idx, binding)| {
+                    let key = if self.rebinding == Some(Rebind::Palette(idx)) {
+                        "press a key".to_owned()
+                    } else {
+                        key_name(binding.key)
+                    };
+                    format!("{}: {}", palette_name(binding.entity_type), key)
+                })
+                .chain(vec!["reset to defaults".to_owned(), "back".to_owned()])
+                .collect(),
+        }
+    }
+    fn back(&mut self) {
+        match self.page {
+            Page::Main => self.transition = Some(geng::Transition::Pop),
+            Page::Controls | Page::Palette => {
+                self.page = Page::Main;
+                self.menu = Menu::new();
+            }
+        }
+    }
+    fn handle_action(&mut self, action: MenuAction) {
+        let settings = self.settings.clone();
+        let mut settings = settings.borrow_mut();
+        match (self.page, action) {
+            (_, MenuAction::Back) => {
+                self.back();
+                return;
+            }
+            (Page::Main, MenuAction::Adjust(0, delta)) => {
+                settings.master_volume =
+                    clamp(settings.master_volume + delta as f64 * 0.1, 0.0..=1.0);
+            }
+            (Page::Main, MenuAction::Adjust(1, delta)) => {
+                settings.sfx_volume = clamp(settings.sfx_volume + delta as f64 * 0.1, 0.0..=1.0);
+            }
+            (Page::Main, MenuAction::Adjust(2, delta)) => {
+                settings.animation_speed =
+                    clamp(settings.animation_speed + delta as f32 * 0.25, 0.25..=3.0);
+            }
+            (Page::Main, MenuAction::Adjust(3, delta)) => {
+                settings.repeat_delay =
+                    clamp(settings.repeat_delay + delta as f32 * 0.05, 0.1..=1.0);
+            }
+            (Page::Main, MenuAction::Adjust(4, delta)) => {
+                settings.repeat_interval =
+                    clamp(settings.repeat_interval + delta as f32 * 0.05, 0.05..=0.5);
+            }
+            (Page::Main, MenuAction::Select(5)) | (Page::Main, MenuAction::Adjust(5, _)) => {
+                settings.fullscreen = !settings.fullscreen;
+                settings.apply(&self.geng);
+            }
+            (Page::Main, MenuAction::Select(6)) | (Page::Main, MenuAction::Adjust(6, _)) => {
+                settings.solver_dead_ends = !settings.solver_dead_ends;
+            }
+            (Page::Main, MenuAction::Select(7)) => {
+                self.page = Page::Controls;
+                self.menu = Menu::new();
+            }
+            (Page::Main, MenuAction::Select(8)) => {
+                self.page = Page::Palette;
+                self.menu = Menu::new();
+            }
+            (Page::Main, MenuAction::Select(9)) => {
+                self.back();
+                return;
+            }
+            (Page::Controls, MenuAction::Select(0))
+            | (Page::Controls, MenuAction::Adjust(0, _)) => {
+                let delta = match action {
+                    MenuAction::Adjust(_, delta) => delta,
+                    _ => 1,
+                };
+                let presets: Vec<MovementPreset> = MovementPreset::into_enum_iter().collect();
+                let current = settings
+                    .bindings
+                    .movement_preset()
+                    .and_then(|preset| presets.iter().position(|&other| other == preset))
+                    .unwrap_or(0) as i32;
+                let next = (current + delta).rem_euclid(presets.len() as i32) as usize;
+                let conflicts = settings.bindings.set_movement_preset(presets[next]);
+                self.notice = notice(conflicts);
+            }
+            (Page::Controls, MenuAction::Select(idx)) => {
+                let count = Action::into_enum_iter().count();
+                if idx <= count {
+                    self.rebinding = Action::into_enum_iter().nth(idx - 1).map(Rebind::Action);
+                } else if idx == count + 1 {
+                    let defaults = &self.assets.bindings;
+                    settings.bindings.actions = defaults.actions.clone();
+                    settings.bindings.gamepad = defaults.gamepad.clone();
+                } else {
+                    self.back();
+                    return;
+                }
+            }
+            (Page::Palette, MenuAction::Select(idx)) => {
+                let count = settings.bindings.palette.len();
+                if idx < count {
+                    self.rebinding = Some(Rebind::Palette(idx));
+                } else if idx == count {
+                    settings.bindings.palette = self.assets.bindings.palette.clone();
+                } else {
+                    self.back();
+                    return;
+                }
+            }
+            _ => return,
+        }
+        settings.save();
+    }
+}
+
+impl geng::State for SettingsScreen {
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::clear(framebuffer, Some(MENU_BACKGROUND), None);
+        let title = match self.page {
+            Page::Main => "settings",
+            Page::Controls => "controls",
+            Page::Palette => "editor palette",
+        };
+        self.menu.draw(
+            &self.renderer,
+            &self.assets.font,
+            framebuffer,
+            title,
+            &self.items(),
+        );
+        if let Some(notice) = &self.notice {
+            self.menu.draw_footer(
+                &self.renderer,
+                &self.assets.font,
+                framebuffer,
+                self.items().len(),
+                &[notice.clone()],
+            );
+        }
+    }
+    fn update(&mut self, _delta_time: f64) {
+        let buttons = self.gamepad.borrow_mut().poll();
+        // Only keys can be rebound, buttons pressed while waiting for one are dropped
+        if self.rebinding.is_some() {
+            return;
+        }
+        for button in buttons {
+            let action = self.settings.borrow().bindings.button_action(button);
+            if let Some(action) = action {
+                let count = self.items().len();
+                if let Some(action) = self.menu.handle_action(action, count) {
+                    self.notice = None;
+                    self.handle_action(action);
+                }
+            }
+        }
+    }
+    fn handle_event(&mut self, event: geng::Event) {
+        if let Some(rebind) = self.rebinding {
+            if let geng::Event::KeyDown { key } = event {
+                if key != geng::Key::Escape {
+                    let mut settings = self.settings.borrow_mut();
+                    match rebind {
+                        Rebind::Action(action) => {
+                            self.notice = notice(settings.bindings.rebind(action, key));
+                        }
+                        Rebind::Palette(idx) => {
+                            self.notice = notice(settings.bindings.rebind_palette(idx, key));
+                        }
+                    }
+                    settings.save();
+                }
+                self.rebinding = None;
+            }
+            return;
+        }
+        let count = self.items().len();
+        let action = self.menu.handle_event(
+            &event,
+            &self.settings.borrow().bindings,
+            self.framebuffer_size,
+            count,
+        );
+        if let Some(action) = action {
+            self.notice = None;
+            self.handle_action(action);
+        }
+    }
+    fn transition(&mut self) -> Option<geng::Transition> {
+        self.transition.take()
+    }
+}
+
+fn notice(conflicts: Vec<String>) -> Option<String> {
+    if conflicts.is_empty() {
+        None
+    } else {
+        Some(conflicts.join("\n"))
+    }
+}
@@ -4,6 +4,8 @@ pub struct LevelRenderer {
     pub renderer: Renderer,
     noise: noise::OpenSimplex,
     assets: Rc<Assets>,
+    effects: Effects,
+    white: ugli::Texture,
 }
 
 impl LevelRenderer {
@@ -12,53 +14,343 @@ impl LevelRenderer {
             renderer: Renderer::new(geng),
             assets: assets.clone(),
             noise: noise::OpenSimplex::new(),
+            effects: Effects::new(),
+            white: ugli::Texture::new_with(geng.ugli(), vec2(1, 1), |_| Color::WHITE),
         }
     }
+    pub fn update(&mut self, delta_time: f32) {
+        self.effects.update(delta_time);
+    }
+    pub fn handle_events(&mut self, events: &[TurnEvent]) {
+        self.effects.handle_events(events);
+    }
     pub fn draw(&self, level: &Level, camera: &Camera, framebuffer: &mut ugli::Framebuffer) {
         let framebuffer_size = framebuffer.size().map(|x| x as f32);
         let tile_low_left_pos = tile_pos(camera.screen_to_world(framebuffer_size, vec2(0.0, 0.0)));
         let tile_top_right_pos =
             tile_pos(camera.screen_to_world(framebuffer_size, framebuffer_size));
+        let mut batch = SpriteBatch::new();
+        let mut overlays: Vec<(Mat4<f32>, Mat4<f32>, &ugli::Texture, Color<f32>)> = Vec::new();
         for x in tile_low_left_pos.x..=tile_top_right_pos.x {
             for y in tile_low_left_pos.y..=tile_top_right_pos.y {
                 let tile_pos = vec2(x, y);
-                self.renderer.draw(
-                    framebuffer,
-                    &camera,
+                batch.add(
                     Mat4::translate(tile_pos.map(|x| x as f32).extend(0.0)),
                     Mat4::identity(),
                     &self.assets.grass,
                     Color::WHITE,
                 );
-                if true || level.get_entity(tile_pos).is_none() {
+                let terrain = level.terrain_at(tile_pos);
+                match terrain {
+                    Terrain::Grass => {}
+                    Terrain::Water => {
+                        let mask = NeighbourMask::from_fn(|direction| {
+                            matches!(
+                                level.terrain_at(tile_pos + direction),
+                                Terrain::Water | Terrain::Bridge
+                            )
+                        });
+                        overlays.push((
+                            Mat4::translate(tile_pos.map(|x| x as f32).extend(0.0)),
+                            mask.texture_matrix(),
+                            &self.assets.water_tiles,
+                            Color::WHITE,
+                        ));
+                    }
+                    Terrain::Bridge => overlays.push((
+                        Mat4::translate(tile_pos.map(|x| x as f32).extend(0.0)),
+                        Mat4::identity(),
+                        &self.assets.bridge,
+                        Color::WHITE,
+                    )),
+                    _ => overlays.push((
+                        Mat4::translate(tile_pos.map(|x| x as f32).extend(0.0)),
+                        Mat4::identity(),
+                        &self.white,
+                        terrain_color(terrain),
+                    )),
+                }
+                if terrain == Terrain::Grass {
                     let nv = noise::NoiseFn::get(&self.noise, [x as f64 + 0.5, y as f64 + 0.5]);
                     let nv = nv / 0.55;
                     let mx = self.assets.flower.len() as i32 + 10;
                     let idx = clamp(((nv + 1.0) / 2.0 * mx as f64) as i32, 0..=mx);
                     if let Some(texture) = self.assets.flower.get(idx as usize) {
-                        self.renderer.draw(
-                            framebuffer,
-                            &camera,
+                        overlays.push((
                             Mat4::translate(tile_pos.map(|x| x as f32).extend(0.0)),
                             // * Mat4::scale_uniform(0.3),
                             Mat4::identity(),
                             texture,
                             Color::WHITE,
-                        );
+                        ));
                     }
                 }
             }
         }
+        batch.add_unordered(overlays);
 
-        for entity in level.entities.values() {
-            self.renderer.draw(
-                framebuffer,
-                &camera,
-                Mat4::translate(entity.render_pos.extend(0.0)),
+        let auto_tiled: HashMap<Vec2<i32>, EntityType> = level
+            .entities
+            .values()
+            .filter(|entity| self.assets.auto_tiles(entity.entity_type).is_some())
+            .map(|entity| (entity.position, entity.entity_type))
+            .collect();
+        // Obstacles never move or share a tile, so only the rest needs sorting by depth
+        let obstacles = level
+            .entities
+            .values()
+            .filter(|entity| entity.entity_type.property() == Some(EntityProperty::Collidable))
+            .map(|entity| match self.assets.auto_tiles(entity.entity_type) {
+                Some(texture) => {
+                    let mask = NeighbourMask::from_fn(|direction| {
+                        auto_tiled.get(&(entity.position + direction)) == Some(&entity.entity_type)
+                    });
+                    (
+                        Mat4::translate(entity.render_pos.extend(0.0)),
+                        mask.texture_matrix(),
+                        texture,
+                        Color::WHITE,
+                    )
+                }
+                None => (
+                    Mat4::translate(entity.render_pos.extend(0.0)),
+                    Mat4::identity(),
+                    self.assets.entity(entity.entity_type),
+                    Color::WHITE,
+                ),
+            })
+            .collect();
+        batch.add_unordered(obstacles);
+        for (_, entity) in draw_order(level) {
+            batch.add(
+                entity_matrix(entity),
                 Mat4::identity(),
                 self.assets.entity(entity.entity_type),
                 Color::WHITE,
             );
         }
+        self.effects.draw(&self.assets, &self.white, &mut batch);
+        if let Some(bounds) = &level.bounds {
+            self.draw_bounds(bounds, &mut batch);
+        }
+        self.renderer.draw_batch(framebuffer, camera, &batch);
+    }
+    /// Border just outside of the playable area, colored by the edge behaviour.
+    fn draw_bounds<'a>(&'a self, bounds: &LevelBounds, batch: &mut SpriteBatch<'a>) {
+        let color = match bounds.edge {
+            EdgeBehaviour::Solid => Color::rgba(0.2, 0.15, 0.1, 0.8),
+            EdgeBehaviour::Wrap => Color::rgba(0.2, 0.5, 1.0, 0.8),
+            EdgeBehaviour::Despawn => Color::rgba(1.0, 0.2, 0.2, 0.8),
+        };
+        let min = bounds.min.map(|x| x as f32);
+        let max = bounds.max.map(|x| x as f32 + 1.0);
+        let thickness = 0.1;
+        for (corner, other_corner) in vec![
+            (
+                min - vec2(thickness, thickness),
+                vec2(max.x + thickness, min.y),
+            ),
+            (
+                vec2(min.x - thickness, max.y),
+                max + vec2(thickness, thickness),
+            ),
+            (min - vec2(thickness, 0.0), vec2(min.x, max.y)),
+            (vec2(max.x, min.y), max + vec2(thickness, 0.0)),
+        ] {
+            let aabb = AABB::from_corners(corner, other_corner);
+            batch.add(
+                Mat4::translate(vec3(aabb.x_min, aabb.y_min, 0.0))
+                    * Mat4::scale(vec3(aabb.width(), aabb.height(), 1.0)),
+                Mat4::identity(),
+                &self.white,
+                color,
+            );
+        }
+    }
+    /// Translucent copies of everything the previewed turn would move,
+    /// and a red tint on whatever it would get eaten.
+    pub fn draw_ghosts(
+        &self,
+        level: &Level,
+        preview: &Level,
+        camera: &Camera,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let mut batch = SpriteBatch::new();
+        for (id, entity) in draw_order(preview) {
+            let moved = level
+                .entities
+                .get(id)
+                .map_or(true, |current| current.position != entity.position);
+            if moved {
+                batch.add(
+                    Mat4::translate(entity.position.map(|x| x as f32).extend(0.0)),
+                    Mat4::identity(),
+                    self.assets.entity(entity.entity_type),
+                    Color::rgba(1.0, 1.0, 1.0, 0.5),
+                );
+            }
+        }
+        for event in &preview.events {
+            match event {
+                TurnEvent::Eaten { entity, .. }
+                | TurnEvent::LeftLevel { entity }
+                | TurnEvent::Sunk { entity } => {
+                    batch.add(
+                        Mat4::translate(entity.position.map(|x| x as f32).extend(0.0)),
+                        Mat4::identity(),
+                        &self.white,
+                        Color::rgba(1.0, 0.0, 0.0, 0.35),
+                    );
+                }
+            }
+        }
+        self.renderer.draw_batch(framebuffer, camera, &batch);
+    }
+    /// Highlights the tile the player should step to, or the player itself for waiting.
+    pub fn draw_hint(
+        &self,
+        level: &Level,
+        hint_move: Move,
+        camera: &Camera,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let player = match level.get_player() {
+            Some(player) => player,
+            None => return,
+        };
+        let tile = (player.position + hint_move.direction()).map(|x| x as f32);
+        let mut batch = SpriteBatch::new();
+        batch.add(
+            Mat4::translate(tile.extend(0.0)),
+            Mat4::identity(),
+            &self.white,
+            Color::rgba(1.0, 1.0, 0.0, 0.4),
+        );
+        self.renderer.draw_batch(framebuffer, camera, &batch);
+    }
+    /// Overlay with the vision, planned move and remembered attractor of every animal.
+    pub fn draw_intents(
+        &self,
+        level: &Level,
+        camera: &Camera,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let intents = level.intents();
+        let mut batch = SpriteBatch::new();
+        let mut rect = |corner: Vec2<f32>, other_corner: Vec2<f32>, color: Color<f32>| {
+            let aabb = AABB::from_corners(corner, other_corner);
+            batch.add(
+                Mat4::translate(vec3(aabb.x_min, aabb.y_min, 0.0))
+                    * Mat4::scale(vec3(aabb.width(), aabb.height(), 1.0)),
+                Mat4::identity(),
+                &self.white,
+                color,
+            );
+        };
+        for intent in &intents {
+            let color = entity_color(intent.entity_type);
+            let radius = intent.view_radius;
+            for dx in -radius..=radius {
+                for dy in -(radius - dx.abs())..=(radius - dx.abs()) {
+                    let tile = (intent.position + vec2(dx, dy)).map(|x| x as f32);
+                    rect(tile, tile + vec2(1.0, 1.0), Color { a: 0.1, ..color });
+                }
+            }
+        }
+        for intent in &intents {
+            let color = entity_color(intent.entity_type);
+            if let Some(attractor_pos) = intent.attractor_pos {
+                let min = attractor_pos.map(|x| x as f32) + vec2(0.1, 0.1);
+                let max = min + vec2(0.8, 0.8);
+                let thickness = 0.06;
+                rect(min, vec2(max.x, min.y + thickness), color);
+                rect(vec2(min.x, max.y - thickness), max, color);
+                rect(min, vec2(min.x + thickness, max.y), color);
+                rect(vec2(max.x - thickness, min.y), max, color);
+            }
+            let center = intent.position.map(|x| x as f32) + vec2(0.5, 0.5);
+            let direction = intent.next_move.direction().map(|x| x as f32);
+            if intent.next_move == Move::Wait {
+                rect(center - vec2(0.08, 0.08), center + vec2(0.08, 0.08), color);
+                continue;
+            }
+            let across = vec2(-direction.y, direction.x);
+            let tip = center + direction * 0.7;
+            rect(center - across * 0.04, tip + across * 0.04, color);
+            // Arrow head made of bars that get narrower towards the tip
+            for i in 0..4 {
+                let along = tip + direction * (i as f32 * 0.06);
+                let half_width = 0.2 - i as f32 * 0.05;
+                rect(
+                    along - across * half_width,
+                    along + direction * 0.06 + across * half_width,
+                    color,
+                );
+            }
+        }
+        self.renderer.draw_batch(framebuffer, camera, &batch);
+    }
+}
+
+/// Animals and pushables from the back row to the front,
+/// so that the result does not depend on the order of the entity map.
+fn draw_order(level: &Level) -> Vec<(&Id, &Entity)> {
+    let mut entities: Vec<(&Id, &Entity)> = level
+        .entities
+        .iter()
+        .filter(|(_, entity)| entity.entity_type.property() != Some(EntityProperty::Collidable))
+        .collect();
+    entities.sort_by_key(|(id, entity)| {
+        (
+            -entity.position.y,
+            entity.position.x,
+            entity.controller.is_some(),
+            id.raw(),
+        )
+    });
+    entities
+}
+
+/// Flat color for terrain without a sprite of its own.
+pub fn terrain_color(terrain: Terrain) -> Color<f32> {
+    match terrain {
+        Terrain::Grass => Color::rgb(0.4, 0.7, 0.3),
+        Terrain::Water => Color::rgb(0.2, 0.5, 0.9),
+        Terrain::Bridge => Color::rgb(0.3, 0.4, 0.5),
+        Terrain::Ice => Color::rgba(0.85, 0.95, 1.0, 0.85),
+        Terrain::Mud => Color::rgba(0.4, 0.25, 0.1, 0.85),
+        Terrain::Hole => Color::rgb(0.08, 0.06, 0.05),
     }
 }
+
+/// Places the entity sprite, adding a hop or squash depending on how it moved
+/// and flipping animals to face the last horizontal direction.
+fn entity_matrix(entity: &Entity) -> Mat4<f32> {
+    let delta = entity.position.map(|x| x as f32) - entity.render_pos;
+    // Long frames and slides leave more than a tile to go, which must not flip the sine
+    let progress = (delta.len().min(1.0) * std::f32::consts::PI).sin();
+    let horizontal = delta.x.abs() > delta.y.abs();
+    let squash = |amount: f32| {
+        let (along, across) = (1.0 - amount * progress, 1.0 + amount * progress / 2.0);
+        if horizontal {
+            vec2(along, across)
+        } else {
+            vec2(across, along)
+        }
+    };
+    let (hop, scale) = match entity.animation.step {
+        Some(StepKind::Walk) => (progress * 0.2, squash(-0.1)),
+        Some(StepKind::Push) => (0.0, squash(0.2)),
+        Some(StepKind::Pushed) => (0.0, squash(0.1)),
+        None => (0.0, vec2(1.0, 1.0)),
+    };
+    let flip = if entity.controller.is_some() && entity.animation.facing_left {
+        -1.0
+    } else {
+        1.0
+    };
+    Mat4::translate((entity.render_pos + vec2(0.5, hop)).extend(0.0))
+        * Mat4::scale(vec3(scale.x * flip, scale.y, 1.0))
+        * Mat4::translate(vec3(-0.5, 0.0, 0.0))
+}
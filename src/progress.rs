@@ -0,0 +1,106 @@
+use super::*;
+
+/// Best result for a level of the pack.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LevelProgress {
+    pub moves: usize,
+    pub stars: usize,
+    pub time: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Progress {
+    /// Keyed by the index of the level in the pack.
+    pub levels: HashMap<usize, LevelProgress>,
+    /// Hints asked for on each level, whether it was finished or not.
+    pub hints: HashMap<usize, usize>,
+}
+
+impl Progress {
+    /// Loads saved progress, starting fresh if there is none.
+    pub fn load() -> Self {
+        storage::load("progress").unwrap_or_default()
+    }
+    pub fn save(&self) {
+        storage::save("progress", self);
+    }
+    pub fn record_hint(&mut self, level: usize) {
+        *self.hints.entry(level).or_insert(0) += 1;
+    }
+    /// Keeps the better of the stored and the new result.
+    /// Returns `true` if the new one is a personal best.
+    pub fn record(&mut self, level: usize, result: &LevelResult) -> bool {
+        let new = LevelProgress {
+            moves: result.moves.len(),
+            stars: result.stars,
+            time: result.time,
+        };
+        match self.levels.get_mut(&level) {
+            Some(best) => {
+                let new_best = new.moves < best.moves;
+                best.stars = best.stars.max(new.stars);
+                best.time = best.time.min(new.time);
+                if new_best {
+                    best.moves = new.moves;
+                }
+                new_best
+            }
+            None => {
+                self.levels.insert(level, new);
+                true
+            }
+        }
+    }
+}
+
+/// Outcome of a single win.
+#[derive(Clone)]
+pub struct LevelResult {
+    pub level_index: usize,
+    pub moves: Vec<Move>,
+    pub par: Option<usize>,
+    pub stars: usize,
+    pub time: f64,
+    pub hints: usize,
+}
+
+impl LevelResult {
+    pub fn new(
+        level_index: usize,
+        moves: Vec<Move>,
+        par: Option<usize>,
+        time: f64,
+        hints: usize,
+    ) -> Self {
+        Self {
+            level_index,
+            stars: stars(moves.len(), par),
+            moves,
+            par,
+            time,
+            hints,
+        }
+    }
+}
+
+pub const MAX_STARS: usize = 3;
+
+/// Three stars for meeting par, two for up to half as many moves again.
+pub fn stars(moves: usize, par: Option<usize>) -> usize {
+    match par {
+        None => MAX_STARS,
+        Some(par) if moves <= par => MAX_STARS,
+        Some(par) if moves * 2 <= par * 3 => 2,
+        Some(_) => 1,
+    }
+}
+
+pub fn stars_text(stars: usize) -> String {
+    format!("{}{}", "*".repeat(stars), "-".repeat(MAX_STARS - stars))
+}
+
+pub fn format_time(time: f64) -> String {
+    let seconds = time.max(0.0);
+    format!("{}:{:04.1}", (seconds / 60.0) as u32, seconds % 60.0)
+}
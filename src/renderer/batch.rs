@@ -0,0 +1,100 @@
+use super::*;
+
+#[derive(ugli::Vertex, Clone)]
+pub struct Vertex {
+    pub a_pos: Vec2<f32>,
+    pub a_vt: Vec2<f32>,
+    pub a_color: Color<f32>,
+}
+
+/// Collects sprites so that runs of sprites sharing a texture are drawn with a single draw call.
+/// Sprites are drawn in the order they were added.
+pub struct SpriteBatch<'a> {
+    vertices: Vec<Vertex>,
+    groups: Vec<(&'a ugli::Texture, std::ops::Range<usize>)>,
+}
+
+impl<'a> SpriteBatch<'a> {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+    /// Adds a unit quad transformed by `matrix`.
+    /// `texture_matrix` maps the quad onto the texture, with `(0, 0)` at the top left.
+    pub fn add(
+        &mut self,
+        matrix: Mat4<f32>,
+        texture_matrix: Mat4<f32>,
+        texture: &'a ugli::Texture,
+        color: Color<f32>,
+    ) {
+        let same_texture = self
+            .groups
+            .last()
+            .map_or(false, |(other, _)| std::ptr::eq(*other, texture));
+        if !same_texture {
+            let start = self.vertices.len();
+            self.groups.push((texture, start..start));
+        }
+        let corner = |x: f32, y: f32| Vertex {
+            a_pos: (matrix * vec4(x, y, 0.0, 1.0)).xy(),
+            a_vt: (texture_matrix * vec4(x, 1.0 - y, 0.0, 1.0)).xy(),
+            a_color: color,
+        };
+        let quad = [
+            corner(0.0, 0.0),
+            corner(1.0, 0.0),
+            corner(1.0, 1.0),
+            corner(0.0, 1.0),
+        ];
+        for &idx in &[0, 1, 2, 0, 2, 3] {
+            self.vertices.push(quad[idx].clone());
+        }
+        self.groups.last_mut().unwrap().1.end = self.vertices.len();
+    }
+    /// Adds sprites that never overlap each other, so their order doesn't matter
+    /// and they can be grouped by texture.
+    pub fn add_unordered(
+        &mut self,
+        mut sprites: Vec<(Mat4<f32>, Mat4<f32>, &'a ugli::Texture, Color<f32>)>,
+    ) {
+        sprites.sort_by_key(|&(_, _, texture, _)| texture as *const ugli::Texture as usize);
+        for (matrix, texture_matrix, texture, color) in sprites {
+            self.add(matrix, texture_matrix, texture, color);
+        }
+    }
+}
+
+impl Renderer {
+    pub fn draw_batch(
+        &self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        batch: &SpriteBatch,
+    ) {
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        let mut vertices = self.vertices.borrow_mut();
+        vertices.clear();
+        vertices.extend_from_slice(&batch.vertices);
+        for (texture, range) in &batch.groups {
+            ugli::draw(
+                framebuffer,
+                &self.program,
+                ugli::DrawMode::Triangles,
+                vertices.slice(range.clone()),
+                (
+                    camera.uniforms(framebuffer_size),
+                    ugli::uniforms! {
+                        u_texture: *texture,
+                    },
+                ),
+                ugli::DrawParameters {
+                    blend_mode: Some(default()),
+                    ..default()
+                },
+            );
+        }
+    }
+}
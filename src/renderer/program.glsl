@@ -1,23 +1,24 @@
 varying vec2 v_vt;
+varying vec4 v_color;
 
 #ifdef VERTEX_SHADER
 attribute vec2 a_pos;
+attribute vec2 a_vt;
+attribute vec4 a_color;
 
-uniform mat4 u_texture_matrix;
 uniform mat4 u_projection_matrix;
 uniform mat4 u_view_matrix;
-uniform mat4 u_model_matrix;
 
 void main() {
-    v_vt = (u_texture_matrix * vec4(a_pos.x, 1.0 - a_pos.y, 0.0, 1.0)).xy;
-    gl_Position = u_projection_matrix * u_view_matrix * u_model_matrix * vec4(a_pos, 0.0, 1.0);
+    v_vt = a_vt;
+    v_color = a_color;
+    gl_Position = u_projection_matrix * u_view_matrix * vec4(a_pos, 0.0, 1.0);
 }
 #endif
 
 #ifdef FRAGMENT_SHADER
 uniform sampler2D u_texture;
-uniform vec4 u_color;
 void main() {
-    gl_FragColor = texture2D(u_texture, v_vt) * u_color;
+    gl_FragColor = texture2D(u_texture, v_vt) * v_color;
 }
-#endif
\ No newline at end of file
+#endif
@@ -0,0 +1,124 @@
+use super::*;
+
+/// Glyphs in the order they appear in `font.png`, row by row.
+/// The glyph right after the last one is used for unknown characters.
+const FONT_CHARS: &str = "abcdefghijklmnopqrstuvwxyz0123456789.,!?'\":;-+/()_=%<>*#&[]@";
+const FONT_COLUMNS: usize = 8;
+const FONT_ROWS: usize = 8;
+const LINE_HEIGHT: f32 = 1.25;
+
+fn glyph_index(c: char) -> Option<usize> {
+    if c.is_whitespace() {
+        return None;
+    }
+    let c = c.to_ascii_lowercase();
+    Some(
+        FONT_CHARS
+            .chars()
+            .position(|other| other == c)
+            .unwrap_or(FONT_CHARS.len()),
+    )
+}
+
+/// Splits text into lines no longer than `max_chars`, breaking on spaces where possible.
+pub fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let line_len = line.chars().count();
+            let word_len = word.chars().count();
+            if line_len > 0 && line_len + 1 + word_len > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            for c in word.chars() {
+                if line.chars().count() == max_chars {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Size of the text drawn with the given glyph size, in world units.
+pub fn measure_text(text: &str, size: f32) -> Vec2<f32> {
+    let lines = text.lines().count().max(1);
+    let width = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    vec2(
+        width as f32 * size,
+        size + (lines - 1) as f32 * size * LINE_HEIGHT,
+    )
+}
+
+impl Renderer {
+    /// Draws text with `pos` at the bottom of the first line.
+    /// Each line is aligned separately: `align` of 0 is left, 0.5 is center and 1 is right.
+    pub fn draw_text(
+        &self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        text: &str,
+        pos: Vec2<f32>,
+        align: f32,
+        size: f32,
+        font: &ugli::Texture,
+        color: Color<f32>,
+    ) {
+        let mut batch = SpriteBatch::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let y = pos.y - line_idx as f32 * size * LINE_HEIGHT;
+            let mut x = pos.x - measure_text(line, size).x * align;
+            for c in line.chars() {
+                if let Some(idx) = glyph_index(c) {
+                    let ty = idx / FONT_COLUMNS;
+                    let tx = idx % FONT_COLUMNS;
+                    batch.add(
+                        Mat4::translate(vec3(x, y, 0.0)) * Mat4::scale_uniform(size),
+                        Mat4::scale(vec3(1.0 / FONT_COLUMNS as f32, 1.0 / FONT_ROWS as f32, 1.0))
+                            * Mat4::translate(vec3(tx as f32, ty as f32, 0.0)),
+                        font,
+                        color,
+                    );
+                }
+                x += size;
+            }
+        }
+        self.draw_batch(framebuffer, camera, &batch);
+    }
+    /// Same as [`Renderer::draw_text`], but wraps lines wider than `max_width`.
+    pub fn draw_text_wrapped(
+        &self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        text: &str,
+        pos: Vec2<f32>,
+        align: f32,
+        size: f32,
+        max_width: f32,
+        font: &ugli::Texture,
+        color: Color<f32>,
+    ) {
+        let max_chars = (max_width / size).floor() as usize;
+        self.draw_text(
+            framebuffer,
+            camera,
+            &wrap_text(text, max_chars).join("\n"),
+            pos,
+            align,
+            size,
+            font,
+            color,
+        );
+    }
+}
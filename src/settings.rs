@@ -0,0 +1,93 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f64,
+    pub sfx_volume: f64,
+    pub animation_speed: f32,
+    /// Seconds a move key has to be held before it starts repeating.
+    pub repeat_delay: f32,
+    /// Seconds between repeated moves while a key is held.
+    pub repeat_interval: f32,
+    pub fullscreen: bool,
+    /// Draw what every animal is going to do next.
+    pub show_intents: bool,
+    /// Also run the solver after every turn to catch unwinnable states.
+    pub solver_dead_ends: bool,
+    pub bindings: KeyBindings,
+}
+
+/// Fields missing from saved settings are filled in from here.
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 0.5,
+            animation_speed: 1.0,
+            repeat_delay: 0.3,
+            repeat_interval: 0.15,
+            fullscreen: false,
+            show_intents: false,
+            solver_dead_ends: false,
+            bindings: default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn new(bindings: &KeyBindings) -> Self {
+        Self {
+            bindings: bindings.clone(),
+            ..default()
+        }
+    }
+    /// Loads saved settings, falling back to defaults if there are none.
+    /// Bindings added since the settings were saved get their default keys.
+    pub fn load(default_bindings: &KeyBindings) -> Self {
+        match storage::load::<Self>("settings") {
+            Some(mut settings) => {
+                settings.bindings.merge_defaults(default_bindings);
+                settings
+            }
+            None => Self::new(default_bindings),
+        }
+    }
+    pub fn save(&self) {
+        storage::save("settings", self);
+    }
+    pub fn apply(&self, geng: &Geng) {
+        if geng.window().is_fullscreen() != self.fullscreen {
+            geng.window().toggle_fullscreen();
+        }
+    }
+    pub fn play(&self, sound: &geng::Sound) {
+        sound
+            .play()
+            .set_volume(self.master_volume * self.sfx_volume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_get_defaults() {
+        let defaults = Settings::default();
+        let settings: Settings = serde_json::from_str(r#"{"sfx_volume": 0.25}"#).unwrap();
+        assert!(settings.sfx_volume == 0.25);
+        assert!(settings.master_volume == defaults.master_volume);
+        assert!(settings.repeat_delay == defaults.repeat_delay);
+        assert!(settings.repeat_interval == defaults.repeat_interval);
+        assert!(!settings.show_intents);
+        assert!(settings.bindings.actions.is_empty());
+    }
+
+    #[test]
+    fn empty_settings_load() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.animation_speed == Settings::default().animation_speed);
+        assert!(!settings.fullscreen);
+    }
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+/// Loads a value stored with `save`, or `None` if there is none yet.
+/// A stored value that fails to parse is reported and treated as missing.
+pub fn load<T: serde::de::DeserializeOwned>(name: &str) -> Option<T> {
+    let json = read(name)?;
+    match serde_json::from_str(&json) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            report(&format!("Failed to parse saved {}: {}", name, e));
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let result = serde_json::to_string_pretty(value)
+        .map_err(|e| e.to_string())
+        .and_then(|json| write(name, &json));
+    if let Err(e) = result {
+        report(&format!("Failed to save {}: {}", name, e));
+    }
+}
+
+/// Saved files go to the per-user config directory, not next to the game.
+#[cfg(not(target_arch = "wasm32"))]
+fn path(name: &str) -> Option<std::path::PathBuf> {
+    let dirs = directories::ProjectDirs::from("", "", "minijam80")?;
+    Some(dirs.config_dir().join(format!("{}.json", name)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(name: &str) -> Option<String> {
+    std::fs::read_to_string(path(name)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(name: &str, json: &str) -> Result<(), String> {
+    let path = path(name).ok_or_else(|| "no config directory".to_owned())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn report(message: &str) {
+    eprintln!("{}", message);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(name: &str) -> Option<String> {
+    local_storage()?.get_item(name).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(name: &str, json: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or_else(|| "no local storage".to_owned())?
+        .set_item(name, json)
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn report(message: &str) {
+    web_sys::console::error_1(&message.into());
+}